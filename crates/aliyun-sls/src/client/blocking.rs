@@ -0,0 +1,155 @@
+use crate::{
+    Log, LogGroupMetadata,
+    client::{GetLogs, GetLogsResponse, HashKey, SlsClient, SlsClientError, Target},
+};
+use serde::de::DeserializeOwned;
+use std::{
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+/// A blocking client for sending logs to Aliyun SLS, built with
+/// [`SlsClientBuilder::build_blocking`](crate::SlsClientBuilder::build_blocking).
+///
+/// Requests are sent on the calling thread, no async runtime is needed.
+/// It must not be used within an async runtime, as it blocks the executor,
+/// and a custom transport must not depend on one either.
+///
+/// Use [`RetryPolicy::blocking`](crate::RetryPolicy::blocking) to retry failed requests.
+#[derive(Clone)]
+pub struct BlockingSlsClient {
+    pub(super) client: SlsClient,
+}
+
+impl BlockingSlsClient {
+    /// Put a log group to the default target of the client.
+    pub fn put_log(&self, metadata: &LogGroupMetadata, logs: &[Log]) {
+        self.try_put_log(metadata, logs).ok();
+    }
+
+    /// Try to put a log group to the default target of the client.
+    pub fn try_put_log(
+        &self,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        block_on(self.client.try_put_log(metadata, logs))
+    }
+
+    /// Put a log group to the default target of the client, routed by the given hash key.
+    pub fn put_log_with_key(&self, hash_key: HashKey, metadata: &LogGroupMetadata, logs: &[Log]) {
+        self.try_put_log_with_key(hash_key, metadata, logs).ok();
+    }
+
+    /// Try to put a log group to the default target of the client, routed by the given hash key.
+    ///
+    /// The hash key overrides the one of the default target, if any.
+    pub fn try_put_log_with_key(
+        &self,
+        hash_key: HashKey,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        block_on(self.client.try_put_log_with_key(hash_key, metadata, logs))
+    }
+
+    /// Put a log group to the given target.
+    pub fn put_log_to(&self, target: &Target, metadata: &LogGroupMetadata, logs: &[Log]) {
+        self.try_put_log_to(target, metadata, logs).ok();
+    }
+
+    /// Try to put a log group to the given target.
+    pub fn try_put_log_to(
+        &self,
+        target: &Target,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        block_on(self.client.try_put_log_to(target, metadata, logs))
+    }
+
+    /// Query logs of the default target of the client, see [`SlsClient::get_logs`].
+    pub fn get_logs<T: DeserializeOwned>(
+        &self,
+        request: &GetLogs,
+    ) -> Result<GetLogsResponse<T>, SlsClientError> {
+        block_on(self.client.get_logs(request))
+    }
+
+    /// Query logs of the given target, see [`SlsClient::get_logs_from`].
+    pub fn get_logs_from<T: DeserializeOwned>(
+        &self,
+        target: &Target,
+        request: &GetLogs,
+    ) -> Result<GetLogsResponse<T>, SlsClientError> {
+        block_on(self.client.get_logs_from(target, request))
+    }
+}
+
+/// Poll the future on the current thread until it completes.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        MayStaticKey, RetryPolicy, SlsClientBuilderError,
+        proto::*,
+        test_utils::{FakeTransport, test_client},
+        transport::HttpResponse,
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_blocking() {
+        let attempts = AtomicU32::new(0);
+        let transport = FakeTransport::new(move |_| {
+            let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+            HttpResponse::new(if attempt == 0 { 503 } else { 200 })
+        });
+        let builder = || {
+            test_client()
+                .project("playground")
+                .logstore("test")
+                .transport(transport.clone())
+        };
+        let err = builder()
+            .retry_policy(RetryPolicy::new(|_| async {}))
+            .build_blocking()
+            .err();
+        assert!(matches!(
+            err,
+            Some(SlsClientBuilderError::NonBlockingRetryPolicy)
+        ));
+
+        let client = builder()
+            .retry_policy(RetryPolicy::blocking().with_base_delay(Default::default()))
+            .build_blocking()
+            .unwrap();
+
+        let logs = vec![Log::default().with(MayStaticKey::from_static("message"), "hello world")];
+        client
+            .try_put_log(&LogGroupMetadata::default(), &logs)
+            .unwrap();
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
@@ -1,32 +1,96 @@
-use crate::client::{SlsClient, SlsClientInner, signer};
-use hmac::{Hmac, Mac};
-use sha1::Sha1;
+use crate::{
+    client::{
+        Compression, HashKey, RetryPolicy, SignatureVersion, SlsClient, SlsClientInner, Target,
+        endpoint::{Endpoint, Origin},
+        imp, naming, signer,
+    },
+    credentials::{Credentials, CredentialsCache, CredentialsProvider},
+    transport::{HttpBackend, HttpConfig, HttpTransport, TransportError},
+};
 use std::sync::Arc;
 
+#[cfg(feature = "blocking")]
+use crate::client::BlockingSlsClient;
+
 /// Builder error.
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum SlsClientBuilderError {
     /// Invalid access secret length.
+    #[deprecated(note = "never returned, see `InvalidAccessSecret`")]
     #[error("invalid access secret length")]
     Hmac,
+    /// Invalid access secret, it must be valid UTF-8.
+    #[error("invalid access secret: must be valid UTF-8")]
+    InvalidAccessSecret,
     /// Missing required field in the builder.
     #[error("missing required field: {0}")]
     Missing(&'static str),
+    /// Invalid base url, it must be of the form `http[s]://host[:port][/path]`.
+    #[error("invalid base url: {0}")]
+    InvalidBaseUrl(String),
+    /// Invalid endpoint.
+    #[error("invalid endpoint {endpoint:?}: {reason}")]
+    InvalidEndpoint {
+        /// The endpoint.
+        endpoint: String,
+        /// Why the endpoint is invalid.
+        reason: &'static str,
+    },
+    /// Invalid project name.
+    #[error("invalid project name {name:?}: {reason}")]
+    InvalidProject {
+        /// The project name.
+        name: String,
+        /// Which naming rule is broken.
+        reason: &'static str,
+    },
+    /// Invalid logstore name.
+    #[error("invalid logstore name {name:?}: {reason}")]
+    InvalidLogstore {
+        /// The logstore name.
+        name: String,
+        /// Which naming rule is broken.
+        reason: &'static str,
+    },
+    /// Invalid shard key, which must be 32 hex digits.
+    #[error("invalid shard key {0:?}: must be 32 hex digits")]
+    InvalidShardKey(String),
+    /// The [`HttpConfig`] is rejected by the HTTP backend, e.g. a malformed proxy url.
+    #[error("invalid http config: {0}")]
+    InvalidHttpConfig(#[source] TransportError),
+    /// The [`HttpConfig`] sets an option which is not supported by the HTTP backend.
+    #[error("http config option {0} is not supported by the http backend")]
+    UnsupportedHttpConfig(&'static str),
+    /// The retry policy of a blocking client was not created with
+    /// [`RetryPolicy::blocking`].
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+    #[error("the retry policy of a blocking client must be created with RetryPolicy::blocking")]
+    NonBlockingRetryPolicy,
 }
 
 /// Builder for creating an SLS client with required and optional parameters.
 pub struct SlsClientBuilder<'a> {
     access_key: Option<String>,
-    hmac: Option<Hmac<Sha1>>,
-    endpoint: Option<&'a str>,
+    access_secret: Option<String>,
+    credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+    endpoint: Option<Endpoint>,
+    base_url: Option<&'a str>,
+    path_style: bool,
     project: Option<&'a str>,
     logstore: Option<&'a str>,
     shard_key: Option<&'a str>,
+    hash_key: Option<HashKey>,
+    signature_version: SignatureVersion,
+    region: Option<&'a str>,
     enable_trace: bool,
     print_internal_error: bool,
-    #[cfg(feature = "deflate")]
-    compression_level: u8,
+    compression: Compression,
+    retry_policy: Option<RetryPolicy>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    http_backend: HttpBackend,
+    http_config: HttpConfig,
 }
 
 type Result<T, E = SlsClientBuilderError> = std::result::Result<T, E>;
@@ -35,15 +99,24 @@ impl Default for SlsClientBuilder<'_> {
     fn default() -> Self {
         Self {
             access_key: None,
-            hmac: None,
+            access_secret: None,
+            credentials_provider: None,
             endpoint: None,
+            base_url: None,
+            path_style: false,
             project: None,
             logstore: None,
             shard_key: None,
+            hash_key: None,
+            signature_version: SignatureVersion::default(),
+            region: None,
             enable_trace: true,
             print_internal_error: false,
-            #[cfg(feature = "deflate")]
-            compression_level: 6,
+            compression: Compression::default(),
+            retry_policy: None,
+            transport: None,
+            http_backend: HttpBackend::default(),
+            http_config: HttpConfig::default(),
         }
     }
 }
@@ -56,35 +129,102 @@ impl<'a> SlsClientBuilder<'a> {
     }
 
     /// Set the access secret for the SLS client.
+    ///
+    /// Fails with [`SlsClientBuilderError::InvalidAccessSecret`] if it is not valid UTF-8.
     pub fn access_secret(mut self, access_secret: impl AsRef<[u8]>) -> Result<Self> {
-        self.hmac = Some(
-            Hmac::<Sha1>::new_from_slice(access_secret.as_ref())
-                .map_err(|_| SlsClientBuilderError::Hmac)?,
-        );
+        let access_secret = std::str::from_utf8(access_secret.as_ref())
+            .map_err(|_| SlsClientBuilderError::InvalidAccessSecret)?;
+        self.access_secret = Some(access_secret.to_string());
         Ok(self)
     }
 
+    /// Set the credentials provider for the SLS client.
+    ///
+    /// Takes precedence over [`access_key`](Self::access_key) and
+    /// [`access_secret`](Self::access_secret).
+    pub fn credentials_provider(mut self, provider: impl CredentialsProvider) -> Self {
+        self.credentials_provider = Some(Arc::new(provider));
+        self
+    }
+
     /// Set the endpoint for the SLS client.
-    pub fn endpoint(mut self, endpoint: &'a str) -> Self {
-        self.endpoint = Some(endpoint);
+    ///
+    /// Either an [`Endpoint`], or the host of the endpoint, e.g. `cn-hangzhou.log.aliyuncs.com`.
+    pub fn endpoint(mut self, endpoint: impl Into<Endpoint>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set the base url requests are sent to, e.g. `http://127.0.0.1:8080`.
+    ///
+    /// Defaults to `https://{endpoint}`. Plain HTTP, explicit ports and a path prefix
+    /// are allowed, so requests can go through a reverse proxy or to a local fake server.
+    /// The [`endpoint`](Self::endpoint) is not required if a base url is set.
+    pub fn base_url(mut self, base_url: &'a str) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Enable or disable path-style project addressing.
+    ///
+    /// Disabled by default, the project is prepended to the host of the url.
+    /// If enabled, requests are sent to the [`base_url`](Self::base_url) as is,
+    /// and the project is addressed by sending the `Host` header `{project}.{endpoint}`
+    /// explicitly. The endpoint defaults to the host of the base url.
+    pub fn path_style(mut self, path_style: bool) -> Self {
+        self.path_style = path_style;
         self
     }
 
-    /// Set the project for the SLS client.
+    /// Set the project of the default target of the SLS client.
+    ///
+    /// The default target is optional, a client without default target
+    /// writes with [`SlsClient::put_log_to`] only.
     pub fn project(mut self, project: &'a str) -> Self {
         self.project = Some(project);
         self
     }
 
-    /// Set the logstore for the SLS client.
+    /// Set the logstore of the default target of the SLS client.
     pub fn logstore(mut self, logstore: &'a str) -> Self {
         self.logstore = Some(logstore);
         self
     }
 
-    /// Set the shard key for the SLS client.
+    /// Set the shard key of the default target of the SLS client, as 32 hex digits.
+    ///
+    /// See [`hash_key`](Self::hash_key) for a typed key.
     pub fn shard_key(mut self, shard_key: &'a str) -> Self {
         self.shard_key = Some(shard_key);
+        self.hash_key = None;
+        self
+    }
+
+    /// Set the hash key of the default target of the SLS client.
+    ///
+    /// Logs are routed to the shard whose hash range contains the key,
+    /// instead of any writable shard.
+    pub fn hash_key(mut self, hash_key: HashKey) -> Self {
+        self.hash_key = Some(hash_key);
+        self.shard_key = None;
+        self
+    }
+
+    /// Set the signature version used to sign requests.
+    ///
+    /// Defaults to [`SignatureVersion::V1`].
+    /// [`SignatureVersion::V4`] also requires the [`region`](Self::region).
+    pub fn signature_version(mut self, signature_version: SignatureVersion) -> Self {
+        self.signature_version = signature_version;
+        self
+    }
+
+    /// Set the region of the endpoint, e.g. `cn-hangzhou`.
+    ///
+    /// Only used by [`SignatureVersion::V4`].
+    /// Defaults to the region of the [`endpoint`](Self::endpoint), if known.
+    pub fn region(mut self, region: &'a str) -> Self {
+        self.region = Some(region);
         self
     }
 
@@ -106,50 +246,183 @@ impl<'a> SlsClientBuilder<'a> {
         self
     }
 
+    /// Set the compression used for log payloads.
+    ///
+    /// Defaults to [`Compression::default`].
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// Set the deflate compression level for the SLS client.
     #[cfg(feature = "deflate")]
     #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    #[deprecated(note = "use `compression(Compression::Deflate { level })` instead")]
     pub fn compression_level(mut self, level: u8) -> Self {
-        self.compression_level = level.clamp(1, 10);
+        self.compression = Compression::Deflate {
+            level: level.clamp(1, 10),
+        };
+        self
+    }
+
+    /// Set the retry policy for failed requests.
+    ///
+    /// Disabled by default, every request is attempted once.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Set the HTTP transport used to send requests.
+    ///
+    /// Defaults to the transport of the [`http_backend`](Self::http_backend),
+    /// configured with [`http_config`](Self::http_config).
+    pub fn transport(mut self, transport: impl HttpTransport) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Set the built-in HTTP backend.
+    ///
+    /// Defaults to [`HttpBackend::default`].
+    /// Ignored if a custom [`transport`](Self::transport) is set.
+    pub fn http_backend(mut self, http_backend: HttpBackend) -> Self {
+        self.http_backend = http_backend;
+        self
+    }
+
+    /// Set the configuration of the built-in HTTP transport.
+    ///
+    /// Ignored if a custom [`transport`](Self::transport) is set.
+    /// Building fails if an option is not supported by the [`http_backend`](Self::http_backend),
+    /// or is invalid, e.g. a malformed proxy url.
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.http_config = http_config;
         self
     }
 
+    /// Build a blocking SLS client with the provided configuration.
+    ///
+    /// Unless a custom [`transport`](Self::transport) is set, requests are sent with
+    /// [`reqwest`](https://docs.rs/reqwest)'s blocking client, configured with
+    /// [`http_config`](Self::http_config).
+    ///
+    /// Requests are polled on the calling thread without an async runtime, so a custom
+    /// transport must not depend on one, e.g. by sending the request when first polled.
+    /// The [`retry_policy`](Self::retry_policy) must be created with [`RetryPolicy::blocking`].
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+    pub fn build_blocking(mut self) -> Result<BlockingSlsClient> {
+        if self
+            .retry_policy
+            .as_ref()
+            .is_some_and(|retry_policy| !retry_policy.is_blocking())
+        {
+            return Err(SlsClientBuilderError::NonBlockingRetryPolicy);
+        }
+        let transport = match self.transport.take() {
+            Some(transport) => transport,
+            None => Arc::new(
+                imp::ReqwestBlockingTransport::new(std::mem::take(&mut self.http_config))
+                    .map_err(SlsClientBuilderError::InvalidHttpConfig)?,
+            ),
+        };
+        self.transport = Some(transport);
+        Ok(BlockingSlsClient {
+            client: self.build()?,
+        })
+    }
+
     /// Build the SLS client with the provided configuration.
     pub fn build(self) -> Result<SlsClient> {
-        let access_key = self
-            .access_key
-            .ok_or(SlsClientBuilderError::Missing("access_key"))?;
-        let hmac = self
-            .hmac
-            .ok_or(SlsClientBuilderError::Missing("access_secret"))?;
-        let endpoint = self
-            .endpoint
-            .ok_or(SlsClientBuilderError::Missing("endpoint"))?;
-        let project = self
-            .project
-            .ok_or(SlsClientBuilderError::Missing("project"))?;
-        let logstore = self
-            .logstore
-            .ok_or(SlsClientBuilderError::Missing("logstore"))?;
-
-        let canonicalized_resource = match self.shard_key {
-            None => format!("/logstores/{logstore}/shards/lb"),
-            Some(shard_key) => format!("/logstores/{logstore}/shards/route?key={shard_key}"),
+        let credentials_provider = match self.credentials_provider {
+            Some(provider) => provider,
+            None => {
+                let access_key = self
+                    .access_key
+                    .ok_or(SlsClientBuilderError::Missing("access_key"))?;
+                let access_secret = self
+                    .access_secret
+                    .ok_or(SlsClientBuilderError::Missing("access_secret"))?;
+                Arc::new(Credentials::new(access_key, access_secret))
+            }
+        };
+        if let Some(endpoint) = &self.endpoint {
+            endpoint
+                .validate()
+                .map_err(|reason| SlsClientBuilderError::InvalidEndpoint {
+                    endpoint: endpoint.to_string(),
+                    reason,
+                })?;
+        }
+        let endpoint = self.endpoint.as_ref().map(Endpoint::host);
+        let origin = match self.base_url {
+            Some(base_url) => Origin::parse(base_url)
+                .ok_or_else(|| SlsClientBuilderError::InvalidBaseUrl(base_url.to_string()))?,
+            None => Origin::new(endpoint.ok_or(SlsClientBuilderError::Missing("endpoint"))?),
+        };
+        let origin = match self.path_style {
+            true => origin.path_style(endpoint),
+            false => origin,
+        };
+        let target = match (self.project, self.logstore) {
+            (None, None) => None,
+            (Some(_), None) => return Err(SlsClientBuilderError::Missing("logstore")),
+            (None, Some(_)) => return Err(SlsClientBuilderError::Missing("project")),
+            (Some(project), Some(logstore)) => {
+                naming::check_project(project).map_err(|reason| {
+                    SlsClientBuilderError::InvalidProject {
+                        name: project.to_string(),
+                        reason,
+                    }
+                })?;
+                naming::check_logstore(logstore).map_err(|reason| {
+                    SlsClientBuilderError::InvalidLogstore {
+                        name: logstore.to_string(),
+                        reason,
+                    }
+                })?;
+                let hash_key = match self.shard_key {
+                    Some(shard_key) => Some(shard_key.parse().map_err(|_| {
+                        SlsClientBuilderError::InvalidShardKey(shard_key.to_string())
+                    })?),
+                    None => self.hash_key,
+                };
+                let target = Target::new(project, logstore);
+                Some(match hash_key {
+                    Some(hash_key) => target.with_hash_key(hash_key),
+                    None => target,
+                })
+            }
         };
 
-        let url = format!("https://{project}.{endpoint}{canonicalized_resource}");
+        let region = match self.signature_version {
+            SignatureVersion::V1 => None,
+            SignatureVersion::V4 => Some(
+                self.region
+                    .or_else(|| self.endpoint.as_ref()?.region_id())
+                    .ok_or(SlsClientBuilderError::Missing("region"))?
+                    .to_string(),
+            ),
+        };
 
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => imp::transport(self.http_backend, self.http_config)?,
+        };
         let client = SlsClientInner {
-            url,
+            origin,
+            target,
+            credentials: CredentialsCache::new(credentials_provider, transport.clone()),
             signer: signer::Signer {
-                hmac,
-                access_key,
-                canonicalized_resource,
+                version: self.signature_version,
+                region,
             },
+            transport,
             enable_trace: self.enable_trace,
             print_internal_error: self.print_internal_error,
-            #[cfg(feature = "deflate")]
-            compression_level: self.compression_level,
+            compression: self.compression,
+            retry_policy: self.retry_policy,
         };
 
         Ok(SlsClient {
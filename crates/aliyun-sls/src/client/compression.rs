@@ -0,0 +1,132 @@
+use crate::proto::DecodeError;
+
+/// Compression applied to the encoded log group before it is sent.
+///
+/// The default is the first enabled codec in the order `lz4`, `deflate`, `zstd`,
+/// falling back to [`Compression::None`] if no codec is compiled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Send the encoded log group as is.
+    None,
+    /// lz4 block compression.
+    #[cfg(feature = "lz4")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lz4")))]
+    Lz4,
+    /// zlib wrapped deflate compression.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    Deflate {
+        /// Compression level, clamped to `1..=10`.
+        level: u8,
+    },
+    /// zstd compression.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    Zstd {
+        /// Compression level, clamped to the range supported by zstd.
+        level: i32,
+    },
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Compression {
+    fn default() -> Self {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "lz4")] {
+                Compression::Lz4
+            } else if #[cfg(feature = "deflate")] {
+                Compression::Deflate { level: 6 }
+            } else if #[cfg(feature = "zstd")] {
+                Compression::Zstd { level: zstd::DEFAULT_COMPRESSION_LEVEL }
+            } else {
+                Compression::None
+            }
+        }
+    }
+}
+
+impl Compression {
+    /// Value of the `x-log-compresstype` header, `None` if uncompressed.
+    pub(crate) fn header_value(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Some("lz4"),
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { .. } => Some("deflate"),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { .. } => Some("zstd"),
+        }
+    }
+
+    /// Codec of the `x-log-compresstype` header value, `None` if unknown or not compiled in.
+    pub(crate) fn from_header_value(value: &str) -> Option<Self> {
+        match value {
+            "" => Some(Compression::None),
+            #[cfg(feature = "lz4")]
+            "lz4" => Some(Compression::Lz4),
+            #[cfg(feature = "deflate")]
+            "deflate" => Some(Compression::Deflate { level: 6 }),
+            #[cfg(feature = "zstd")]
+            "zstd" => Some(Compression::Zstd { level: 0 }),
+            _ => None,
+        }
+    }
+
+    /// Codec requested for responses, the first enabled of `lz4`, `deflate` and `zstd`.
+    pub(crate) fn accept_encoding() -> Option<&'static str> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "lz4")] {
+                Some("lz4")
+            } else if #[cfg(feature = "deflate")] {
+                Some("deflate")
+            } else if #[cfg(feature = "zstd")] {
+                Some("zstd")
+            } else {
+                None
+            }
+        }
+    }
+
+    pub(crate) fn compress(&self, buf: Vec<u8>) -> Vec<u8> {
+        match self {
+            Compression::None => buf,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::compress(&buf),
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { level } => {
+                miniz_oxide::deflate::compress_to_vec_zlib(&buf, (*level).clamp(1, 10))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => {
+                let range = zstd::compression_level_range();
+                let level = (*level).clamp(*range.start(), *range.end());
+                zstd::bulk::compress(&buf, level).expect("infallible")
+            }
+        }
+    }
+
+    /// Decompress a buffer compressed with this codec, `raw_size` bytes long once decompressed.
+    pub(crate) fn decompress(&self, buf: &[u8], raw_size: usize) -> Result<Vec<u8>, DecodeError> {
+        match self {
+            Compression::None if buf.len() == raw_size => Ok(buf.to_vec()),
+            Compression::None => Err(DecodeError::Decompress(
+                format!("expected {raw_size} bytes, got {}", buf.len()).into(),
+            )),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                lz4_flex::decompress(buf, raw_size).map_err(|e| DecodeError::Decompress(e.into()))
+            }
+            #[cfg(feature = "deflate")]
+            Compression::Deflate { .. } => {
+                miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(buf, raw_size)
+                    .map_err(|e| DecodeError::Decompress(e.to_string().into()))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { .. } => {
+                zstd::bulk::decompress(buf, raw_size).map_err(|e| DecodeError::Decompress(e.into()))
+            }
+        }
+    }
+}
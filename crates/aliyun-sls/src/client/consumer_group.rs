@@ -0,0 +1,355 @@
+use crate::{
+    client::{Cursor, SlsClient, SlsClientError, Target, parse_json, validate_target},
+    transport::{HttpResponse, Method},
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Consumer group of a logstore, sharing its shards between consumers.
+///
+/// See <https://help.aliyun.com/zh/sls/user-guide/use-consumer-groups-to-consume-data>.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConsumerGroup {
+    name: String,
+    timeout: u32,
+    order: bool,
+}
+
+/// Checkpoint of a shard in a consumer group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Checkpoint {
+    /// Shard id.
+    pub shard: u32,
+    /// Cursor to resume consuming from, `None` if no checkpoint was committed.
+    pub cursor: Option<Cursor>,
+    /// Consumer which committed the checkpoint.
+    pub consumer: String,
+    /// Time the checkpoint was committed, as a UNIX timestamp in microseconds.
+    pub update_time: u64,
+}
+
+impl ConsumerGroup {
+    /// Create a consumer group, whose consumers are released after 60 seconds without heartbeat.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            timeout: 60,
+            order: false,
+        }
+    }
+
+    /// Set the time in seconds after which a consumer without heartbeat is released,
+    /// its shards being assigned to other consumers.
+    pub fn with_timeout(mut self, timeout: u32) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Consume the logs of a shard split or merged in order,
+    /// the new shards are only assigned once the old one is consumed.
+    pub fn with_order(mut self, order: bool) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Name of the consumer group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Timeout in seconds of a consumer.
+    pub fn timeout(&self) -> u32 {
+        self.timeout
+    }
+
+    /// Whether split or merged shards are consumed in order.
+    pub fn order(&self) -> bool {
+        self.order
+    }
+}
+
+impl SlsClient {
+    /// Create a consumer group in the logstore of the given target.
+    ///
+    /// Fails with [`ErrorCode::ConsumerGroupAlreadyExist`](crate::ErrorCode::ConsumerGroupAlreadyExist)
+    /// if it already exists.
+    pub async fn create_consumer_group(
+        &self,
+        target: &Target,
+        group: &ConsumerGroup,
+    ) -> Result<(), SlsClientError> {
+        let body = json!({
+            "consumerGroup": group.name,
+            "timeout": group.timeout,
+            "order": group.order,
+        });
+        self.consumer_group_request(
+            "create_consumer_group",
+            Method::Post,
+            target,
+            None,
+            BTreeMap::new(),
+            Some(body),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Update the timeout and order of a consumer group.
+    pub async fn update_consumer_group(
+        &self,
+        target: &Target,
+        group: &ConsumerGroup,
+    ) -> Result<(), SlsClientError> {
+        let body = json!({ "timeout": group.timeout, "order": group.order });
+        self.consumer_group_request(
+            "update_consumer_group",
+            Method::Put,
+            target,
+            Some(&group.name),
+            BTreeMap::new(),
+            Some(body),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Delete a consumer group, and its checkpoints.
+    pub async fn delete_consumer_group(
+        &self,
+        target: &Target,
+        group: &str,
+    ) -> Result<(), SlsClientError> {
+        self.consumer_group_request(
+            "delete_consumer_group",
+            Method::Delete,
+            target,
+            Some(group),
+            BTreeMap::new(),
+            None,
+        )
+        .await
+        .map(drop)
+    }
+
+    /// List the consumer groups of the logstore of the given target.
+    pub async fn list_consumer_groups(
+        &self,
+        target: &Target,
+    ) -> Result<Vec<ConsumerGroup>, SlsClientError> {
+        let res = self
+            .consumer_group_request(
+                "list_consumer_groups",
+                Method::Get,
+                target,
+                None,
+                BTreeMap::new(),
+                None,
+            )
+            .await?;
+        parse_json(&res)
+    }
+
+    /// Send a heartbeat of `consumer`, holding the given shards.
+    ///
+    /// Returns the shards assigned to the consumer, which may differ from the held ones:
+    /// new shards should be consumed, and shards no longer assigned should be released
+    /// after committing their checkpoints.
+    pub async fn heartbeat(
+        &self,
+        target: &Target,
+        group: &str,
+        consumer: &str,
+        held_shards: &[u32],
+    ) -> Result<Vec<u32>, SlsClientError> {
+        let query = BTreeMap::from([
+            ("type".to_string(), "heartbeat".to_string()),
+            ("consumer".to_string(), consumer.to_string()),
+        ]);
+        let res = self
+            .consumer_group_request(
+                "heartbeat",
+                Method::Post,
+                target,
+                Some(group),
+                query,
+                Some(json!(held_shards)),
+            )
+            .await?;
+        parse_json(&res)
+    }
+
+    /// Commit the checkpoint of a shard held by `consumer`.
+    pub async fn update_checkpoint(
+        &self,
+        target: &Target,
+        group: &str,
+        consumer: &str,
+        shard: u32,
+        cursor: &Cursor,
+    ) -> Result<(), SlsClientError> {
+        let query = BTreeMap::from([
+            ("type".to_string(), "checkpoint".to_string()),
+            ("consumer".to_string(), consumer.to_string()),
+            ("forceSuccess".to_string(), "true".to_string()),
+        ]);
+        let body = json!({ "shard": shard, "checkpoint": cursor.as_str() });
+        self.consumer_group_request(
+            "update_checkpoint",
+            Method::Post,
+            target,
+            Some(group),
+            query,
+            Some(body),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Get the checkpoint of a shard, or of every shard if `shard` is `None`.
+    pub async fn get_checkpoints(
+        &self,
+        target: &Target,
+        group: &str,
+        shard: Option<u32>,
+    ) -> Result<Vec<Checkpoint>, SlsClientError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Body {
+            shard: u32,
+            checkpoint: String,
+            #[serde(default)]
+            consumer: String,
+            #[serde(default)]
+            update_time: u64,
+        }
+
+        let query = BTreeMap::from([(
+            "shard".to_string(),
+            shard.map_or_else(|| "-1".to_string(), |shard| shard.to_string()),
+        )]);
+        let res = self
+            .consumer_group_request(
+                "get_checkpoints",
+                Method::Get,
+                target,
+                Some(group),
+                query,
+                None,
+            )
+            .await?;
+        Ok(parse_json::<Vec<Body>>(&res)?
+            .into_iter()
+            .map(|body| Checkpoint {
+                shard: body.shard,
+                cursor: (!body.checkpoint.is_empty()).then(|| Cursor::new(body.checkpoint)),
+                consumer: body.consumer,
+                update_time: body.update_time,
+            })
+            .collect())
+    }
+
+    async fn consumer_group_request(
+        &self,
+        operation: &str,
+        method: Method,
+        target: &Target,
+        group: Option<&str>,
+        query: BTreeMap<String, String>,
+        body: Option<serde_json::Value>,
+    ) -> Result<HttpResponse, SlsClientError> {
+        validate_target(target)?;
+        let mut resource = format!("/logstores/{}/consumergroups", target.logstore());
+        if let Some(group) = group {
+            resource.push('/');
+            resource.push_str(group);
+        }
+        // Heartbeats and checkpoints, the only POSTs to a group, can be repeated safely
+        let idempotent = method.is_idempotent() || (method == Method::Post && group.is_some());
+        self.send_json(
+            operation,
+            method,
+            idempotent,
+            target.project(),
+            &resource,
+            &query,
+            body,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConsumerGroup, Cursor, Target,
+        test_utils::{FakeTransport, test_client},
+        transport::{HttpResponse, Method},
+    };
+
+    #[tokio::test]
+    async fn test_consumer_group() {
+        let transport = FakeTransport::new(|request| {
+            let path = request
+                .url
+                .strip_prefix("https://playground.cn-guangzhou.log.aliyuncs.com")
+                .unwrap();
+            let body = std::str::from_utf8(&request.body).unwrap();
+            match (request.method, path) {
+                (Method::Post, "/logstores/test/consumergroups") => {
+                    assert_eq!(
+                        body,
+                        r#"{"consumerGroup":"group","order":false,"timeout":30}"#
+                    );
+                    HttpResponse::new(200)
+                }
+                (
+                    Method::Post,
+                    "/logstores/test/consumergroups/group?consumer=worker&type=heartbeat",
+                ) => {
+                    assert_eq!(body, "[0]");
+                    HttpResponse::new(200).with_body("[0,1]")
+                }
+                (
+                    Method::Post,
+                    "/logstores/test/consumergroups/group?consumer=worker&forceSuccess=true&type=checkpoint",
+                ) => {
+                    assert_eq!(body, r#"{"checkpoint":"MTUwNTQ=","shard":1}"#);
+                    HttpResponse::new(200)
+                }
+                (Method::Get, "/logstores/test/consumergroups/group?shard=-1") => {
+                    HttpResponse::new(200).with_body(
+                        r#"[{"shard":0,"checkpoint":"","updateTime":0,"consumer":""},
+                            {"shard":1,"checkpoint":"MTUwNTQ=","updateTime":1700000000000000,"consumer":"worker"}]"#,
+                    )
+                }
+                _ => HttpResponse::new(404),
+            }
+        });
+        let client = test_client().transport(transport).build().unwrap();
+        let target = Target::new("playground", "test");
+        let cursor = Cursor::new("MTUwNTQ=");
+
+        client
+            .create_consumer_group(&target, &ConsumerGroup::new("group").with_timeout(30))
+            .await
+            .unwrap();
+        let shards = client
+            .heartbeat(&target, "group", "worker", &[0])
+            .await
+            .unwrap();
+        assert_eq!(shards, [0, 1]);
+        client
+            .update_checkpoint(&target, "group", "worker", 1, &cursor)
+            .await
+            .unwrap();
+        let checkpoints = client
+            .get_checkpoints(&target, "group", None)
+            .await
+            .unwrap();
+        assert_eq!(checkpoints[0].cursor, None);
+        assert_eq!(checkpoints[1].cursor, Some(cursor));
+    }
+}
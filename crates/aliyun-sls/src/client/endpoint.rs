@@ -0,0 +1,284 @@
+use std::fmt;
+
+const DOMAIN: &str = "log.aliyuncs.com";
+const ACCELERATE_HOST: &str = "log-global.aliyuncs.com";
+const INTERNAL_SUFFIX: &str = "-intranet";
+
+/// Endpoint of the SLS service.
+///
+/// Either the endpoint of a region, see
+/// <https://help.aliyun.com/zh/sls/developer-reference/api-sls-2020-12-30-endpoint>,
+/// or any custom host.
+///
+/// ```
+/// use aliyun_sls::Endpoint;
+///
+/// let endpoint = Endpoint::region("cn-hangzhou").internal();
+/// assert_eq!(endpoint.host(), "cn-hangzhou-intranet.log.aliyuncs.com");
+/// assert_eq!(endpoint.region_id(), Some("cn-hangzhou"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    host: String,
+    region: Option<String>,
+}
+
+/// Where requests are sent to, and how the project is addressed.
+#[derive(Debug, Clone)]
+pub(crate) struct Origin {
+    scheme: String,
+    /// Host and non-default port requests are sent to.
+    authority: String,
+    /// Path prepended to the resource.
+    prefix: String,
+    /// Endpoint of the `Host` header in path-style, `None` for virtual-hosted style.
+    path_style_host: Option<String>,
+}
+
+impl Endpoint {
+    /// Public endpoint of a region, e.g. `cn-hangzhou.log.aliyuncs.com`.
+    pub fn region(region: impl Into<String>) -> Self {
+        let region = region.into();
+        Self {
+            host: format!("{region}.{DOMAIN}"),
+            region: Some(region),
+        }
+    }
+
+    /// Endpoint with a custom host, e.g. of a private deployment.
+    ///
+    /// The region is inferred from hosts of the form `{region}[-intranet].log.aliyuncs.com`.
+    pub fn custom(host: impl Into<String>) -> Self {
+        let host = host.into();
+        let region = host
+            .strip_suffix(DOMAIN)
+            .and_then(|region| region.strip_suffix('.'))
+            .map(|region| region.strip_suffix(INTERNAL_SUFFIX).unwrap_or(region))
+            .filter(|region| !region.is_empty() && !region.contains('.'))
+            .map(str::to_string);
+        Self { host, region }
+    }
+
+    /// Switch to the public endpoint of the region.
+    ///
+    /// Does nothing for a custom endpoint without region.
+    pub fn public(self) -> Self {
+        match self.region {
+            Some(region) => Self::region(region),
+            None => self,
+        }
+    }
+
+    /// Switch to the internal endpoint of the region, only reachable from the VPC of the region,
+    /// e.g. `cn-hangzhou-intranet.log.aliyuncs.com`.
+    ///
+    /// Does nothing for a custom endpoint without region.
+    pub fn internal(self) -> Self {
+        match self.region {
+            Some(region) => Self {
+                host: format!("{region}{INTERNAL_SUFFIX}.{DOMAIN}"),
+                region: Some(region),
+            },
+            None => self,
+        }
+    }
+
+    /// Switch to the global acceleration endpoint `log-global.aliyuncs.com`,
+    /// keeping the region for signing.
+    ///
+    /// Global acceleration must be enabled for the project.
+    pub fn accelerate(self) -> Self {
+        Self {
+            host: ACCELERATE_HOST.to_string(),
+            region: self.region,
+        }
+    }
+
+    /// Host of the endpoint.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Region of the endpoint, if known.
+    pub fn region_id(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Check the endpoint, returning the reason it is invalid.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        if self.host.is_empty() {
+            return Err("must not be empty");
+        }
+        if self.host.contains("://") {
+            return Err("must be a host without scheme, use `base_url` for a full url");
+        }
+        if self.host.contains(['/', '?', '#', '@']) || self.host.contains(char::is_whitespace) {
+            return Err("must be a host with optional port");
+        }
+        if let Some(region) = &self.region {
+            if region.is_empty()
+                || !region
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+            {
+                return Err("region must be lowercase letters, digits and hyphens");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for Endpoint {
+    fn from(host: &str) -> Self {
+        Self::custom(host)
+    }
+}
+
+impl From<String> for Endpoint {
+    fn from(host: String) -> Self {
+        Self::custom(host)
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.host)
+    }
+}
+
+impl Origin {
+    /// Send requests to `https://{project}.{endpoint}`.
+    pub(crate) fn new(endpoint: &str) -> Self {
+        Self {
+            scheme: "https".to_string(),
+            authority: endpoint.to_string(),
+            prefix: String::new(),
+            path_style_host: None,
+        }
+    }
+
+    /// Send requests to a base url of the form `scheme://host[:port][/prefix]`.
+    ///
+    /// Returns `None` if the scheme is not `http` or `https`, or the host is empty.
+    pub(crate) fn parse(base_url: &str) -> Option<Self> {
+        let (scheme, rest) = base_url.split_once("://")?;
+        let scheme = scheme.to_ascii_lowercase();
+        let default_port = match scheme.as_str() {
+            "http" => ":80",
+            "https" => ":443",
+            _ => return None,
+        };
+        let rest = rest.trim_end_matches('/');
+        let (authority, prefix) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        // The http client omits the default port in the `Host` header, and so must we
+        let authority = authority.strip_suffix(default_port).unwrap_or(authority);
+        if authority.is_empty() || authority.contains(['?', '#', '@']) {
+            return None;
+        }
+        Some(Self {
+            scheme,
+            authority: authority.to_string(),
+            prefix: prefix.to_string(),
+            path_style_host: None,
+        })
+    }
+
+    /// Address the project with the `Host` header `{project}.{endpoint}`,
+    /// instead of the host of the url.
+    pub(crate) fn path_style(mut self, endpoint: Option<&str>) -> Self {
+        self.path_style_host = Some(endpoint.unwrap_or(&self.authority).to_string());
+        self
+    }
+
+    /// Url of the resource of `project`, before the query.
+    pub(crate) fn url(&self, project: &str, resource: &str) -> String {
+        let Origin {
+            scheme,
+            authority,
+            prefix,
+            ..
+        } = self;
+        match self.path_style_host {
+            None => format!("{scheme}://{project}.{authority}{prefix}{resource}"),
+            Some(_) => format!("{scheme}://{authority}{prefix}{resource}"),
+        }
+    }
+
+    /// `Host` header of requests to `project`.
+    pub(crate) fn host(&self, project: &str) -> String {
+        match &self.path_style_host {
+            None => format!("{project}.{}", self.authority),
+            Some(endpoint) => format!("{project}.{endpoint}"),
+        }
+    }
+
+    /// Whether the `Host` header differs from the host of the url, and must be sent explicitly.
+    pub(crate) fn is_path_style(&self) -> bool {
+        self.path_style_host.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        let endpoint = Endpoint::region("cn-hangzhou");
+        assert_eq!(endpoint.host(), "cn-hangzhou.log.aliyuncs.com");
+        let endpoint = endpoint.internal();
+        assert_eq!(endpoint.host(), "cn-hangzhou-intranet.log.aliyuncs.com");
+        let endpoint = endpoint.accelerate();
+        assert_eq!(endpoint.host(), "log-global.aliyuncs.com");
+        assert_eq!(endpoint.region_id(), Some("cn-hangzhou"));
+        let endpoint = endpoint.public();
+        assert_eq!(endpoint.host(), "cn-hangzhou.log.aliyuncs.com");
+
+        let endpoint = Endpoint::from("cn-shanghai-intranet.log.aliyuncs.com");
+        assert_eq!(endpoint.region_id(), Some("cn-shanghai"));
+        assert_eq!(Endpoint::from("127.0.0.1:8080").region_id(), None);
+
+        assert!(Endpoint::region("cn-hangzhou").validate().is_ok());
+        assert!(Endpoint::region("CN Hangzhou").validate().is_err());
+        assert!(
+            Endpoint::from("https://cn-hangzhou.log.aliyuncs.com")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_origin() {
+        let origin = Origin::new("cn-hangzhou.log.aliyuncs.com");
+        assert_eq!(
+            origin.url("project", "/logstores"),
+            "https://project.cn-hangzhou.log.aliyuncs.com/logstores"
+        );
+        assert_eq!(
+            origin.host("project"),
+            "project.cn-hangzhou.log.aliyuncs.com"
+        );
+
+        let origin = Origin::parse("http://cn-hangzhou-intranet.log.aliyuncs.com:80/").unwrap();
+        assert_eq!(
+            origin.url("project", "/logstores"),
+            "http://project.cn-hangzhou-intranet.log.aliyuncs.com/logstores"
+        );
+
+        let origin = Origin::parse("http://127.0.0.1:8080/sls")
+            .unwrap()
+            .path_style(Some("cn-hangzhou.log.aliyuncs.com"));
+        assert_eq!(
+            origin.url("project", "/logstores"),
+            "http://127.0.0.1:8080/sls/logstores"
+        );
+        assert_eq!(
+            origin.host("project"),
+            "project.cn-hangzhou.log.aliyuncs.com"
+        );
+
+        assert!(Origin::parse("ftp://127.0.0.1").is_none());
+        assert!(Origin::parse("127.0.0.1:8080").is_none());
+        assert!(Origin::parse("http:///logstores").is_none());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{credentials::CredentialsError, transport::TransportError};
+use std::{error::Error, fmt, ops::Range};
+
+/// Error type for SLS client operations.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SlsClientError {
+    /// Non-successful HTTP response from the SLS service.
+    #[error(
+        "http error [{status}] {}{message}{}",
+        .code.as_ref().map(|c| format!("{c}: ")).unwrap_or_default(),
+        .request_id.as_ref().map(|id| format!(" (request id: {id})")).unwrap_or_default(),
+    )]
+    Http {
+        /// HTTP status code.
+        status: u16,
+        /// SLS error code, if the response body is a SLS error.
+        code: Option<ErrorCode>,
+        /// Error message from the response.
+        ///
+        /// This is the `errorMessage` of a SLS error, or the raw response body otherwise.
+        message: Box<str>,
+        /// Value of the `x-log-requestid` response header.
+        request_id: Option<Box<str>>,
+    },
+    /// The request did not get a response.
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+    /// The target is invalid, or no target is given to a client without default target.
+    #[error("invalid target: {0}")]
+    InvalidTarget(Box<str>),
+    /// An argument of the request is invalid, e.g. a split key outside the hash range of the shard.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(Box<str>),
+    /// Failed to load credentials.
+    #[error(transparent)]
+    Credentials(#[from] CredentialsError),
+    /// The request requires a feature which is not enabled.
+    #[error("unsupported request: {0}")]
+    Unsupported(&'static str),
+    /// The response body or headers could not be parsed.
+    #[error("invalid response: {0}")]
+    InvalidResponse(#[source] Box<dyn Error + Send + Sync>),
+    /// A log group over the PutLogs limits was split into chunks, and only some of them were sent.
+    #[error("{} of the logs failed to be sent: {source}", .failed.iter().map(|r| r.len()).sum::<usize>())]
+    PartialFailure {
+        /// Index ranges of the logs which failed to be sent.
+        failed: Vec<Range<usize>>,
+        /// Error of the first chunk which failed to be sent.
+        source: Box<SlsClientError>,
+    },
+}
+
+/// Error code returned by the SLS service.
+///
+/// See <https://help.aliyun.com/zh/sls/developer-reference/error-codes> for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The request is not authorized.
+    Unauthorized,
+    /// The access key id does not exist.
+    InvalidAccessKeyId,
+    /// The request signature does not match.
+    SignatureNotMatch,
+    /// The `Date` of the request is too far from the server time.
+    RequestTimeExpired,
+    /// The security token is expired.
+    SecurityTokenExpired,
+    /// The project does not exist.
+    ProjectNotExist,
+    /// The logstore already exists.
+    LogStoreAlreadyExist,
+    /// The logstore does not exist.
+    LogStoreNotExist,
+    /// The shard does not exist.
+    ShardNotExist,
+    /// The write quota of the project is exceeded.
+    WriteQuotaExceed,
+    /// The write quota of the shard is exceeded.
+    ShardWriteQuotaExceed,
+    /// The read quota of the project is exceeded.
+    ReadQuotaExceed,
+    /// The read quota of the shard is exceeded.
+    ShardReadQuotaExceed,
+    /// The request body is too large.
+    PostBodyTooLarge,
+    /// The request body is invalid.
+    PostBodyInvalid,
+    /// The request body cannot be decompressed.
+    PostBodyUncompressError,
+    /// A request parameter is invalid.
+    ParameterInvalid,
+    /// The index of the logstore already exists.
+    IndexAlreadyExist,
+    /// The logstore has no index.
+    IndexConfigNotExist,
+    /// The consumer group already exists.
+    ConsumerGroupAlreadyExist,
+    /// The consumer group does not exist.
+    ConsumerGroupNotExist,
+    /// The server is busy.
+    ServerBusy,
+    /// Internal server error.
+    InternalServerError,
+    /// Any other error code.
+    Other(Box<str>),
+}
+
+impl SlsClientError {
+    /// HTTP status code, if the error is a non-successful response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            SlsClientError::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// SLS error code, if the error is a SLS error response.
+    pub fn code(&self) -> Option<&ErrorCode> {
+        match self {
+            SlsClientError::Http { code, .. } => code.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// SLS request id, if the error is a non-successful response.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            SlsClientError::Http { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_response(status: u16, request_id: Option<String>, body: String) -> Self {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ErrorBody {
+            error_code: String,
+            error_message: String,
+        }
+
+        let (code, message) = match serde_json::from_str::<ErrorBody>(&body) {
+            Ok(err) => (
+                Some(ErrorCode::from(err.error_code.as_str())),
+                err.error_message.into_boxed_str(),
+            ),
+            Err(_) => (None, body.into_boxed_str()),
+        };
+        SlsClientError::Http {
+            status,
+            code,
+            message,
+            request_id: request_id.map(String::into_boxed_str),
+        }
+    }
+}
+
+impl ErrorCode {
+    /// Returns the error code as it appears in the response.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorCode::Unauthorized => "Unauthorized",
+            ErrorCode::InvalidAccessKeyId => "InvalidAccessKeyId",
+            ErrorCode::SignatureNotMatch => "SignatureNotMatch",
+            ErrorCode::RequestTimeExpired => "RequestTimeExpired",
+            ErrorCode::SecurityTokenExpired => "SecurityTokenExpired",
+            ErrorCode::ProjectNotExist => "ProjectNotExist",
+            ErrorCode::LogStoreAlreadyExist => "LogStoreAlreadyExist",
+            ErrorCode::LogStoreNotExist => "LogStoreNotExist",
+            ErrorCode::ShardNotExist => "ShardNotExist",
+            ErrorCode::WriteQuotaExceed => "WriteQuotaExceed",
+            ErrorCode::ShardWriteQuotaExceed => "ShardWriteQuotaExceed",
+            ErrorCode::ReadQuotaExceed => "ReadQuotaExceed",
+            ErrorCode::ShardReadQuotaExceed => "ShardReadQuotaExceed",
+            ErrorCode::PostBodyTooLarge => "PostBodyTooLarge",
+            ErrorCode::PostBodyInvalid => "PostBodyInvalid",
+            ErrorCode::PostBodyUncompressError => "PostBodyUncompressError",
+            ErrorCode::ParameterInvalid => "ParameterInvalid",
+            ErrorCode::IndexAlreadyExist => "IndexAlreadyExist",
+            ErrorCode::IndexConfigNotExist => "IndexConfigNotExist",
+            ErrorCode::ConsumerGroupAlreadyExist => "ConsumerGroupAlreadyExist",
+            ErrorCode::ConsumerGroupNotExist => "ConsumerGroupNotExist",
+            ErrorCode::ServerBusy => "ServerBusy",
+            ErrorCode::InternalServerError => "InternalServerError",
+            ErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<&str> for ErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "Unauthorized" => ErrorCode::Unauthorized,
+            "InvalidAccessKeyId" => ErrorCode::InvalidAccessKeyId,
+            "SignatureNotMatch" => ErrorCode::SignatureNotMatch,
+            "RequestTimeExpired" => ErrorCode::RequestTimeExpired,
+            "SecurityTokenExpired" => ErrorCode::SecurityTokenExpired,
+            "ProjectNotExist" => ErrorCode::ProjectNotExist,
+            "LogStoreAlreadyExist" => ErrorCode::LogStoreAlreadyExist,
+            "LogStoreNotExist" => ErrorCode::LogStoreNotExist,
+            "ShardNotExist" => ErrorCode::ShardNotExist,
+            "WriteQuotaExceed" => ErrorCode::WriteQuotaExceed,
+            "ShardWriteQuotaExceed" => ErrorCode::ShardWriteQuotaExceed,
+            "ReadQuotaExceed" => ErrorCode::ReadQuotaExceed,
+            "ShardReadQuotaExceed" => ErrorCode::ShardReadQuotaExceed,
+            "PostBodyTooLarge" => ErrorCode::PostBodyTooLarge,
+            "PostBodyInvalid" => ErrorCode::PostBodyInvalid,
+            "PostBodyUncompressError" => ErrorCode::PostBodyUncompressError,
+            "ParameterInvalid" => ErrorCode::ParameterInvalid,
+            "IndexAlreadyExist" => ErrorCode::IndexAlreadyExist,
+            "IndexConfigNotExist" => ErrorCode::IndexConfigNotExist,
+            "ConsumerGroupAlreadyExist" => ErrorCode::ConsumerGroupAlreadyExist,
+            "ConsumerGroupNotExist" => ErrorCode::ConsumerGroupNotExist,
+            "ServerBusy" => ErrorCode::ServerBusy,
+            "InternalServerError" => ErrorCode::InternalServerError,
+            other => ErrorCode::Other(other.into()),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        let err = SlsClientError::from_response(
+            403,
+            Some("5F0C6E2A1D2C3B4A".to_string()),
+            r#"{"errorCode":"WriteQuotaExceed","errorMessage":"Project write quota exceed"}"#
+                .to_string(),
+        );
+        assert_eq!(err.status(), Some(403));
+        assert_eq!(err.code(), Some(&ErrorCode::WriteQuotaExceed));
+        assert_eq!(err.request_id(), Some("5F0C6E2A1D2C3B4A"));
+        assert_eq!(
+            err.to_string(),
+            "http error [403] WriteQuotaExceed: Project write quota exceed (request id: 5F0C6E2A1D2C3B4A)"
+        );
+
+        let err = SlsClientError::from_response(502, None, "Bad Gateway".to_string());
+        assert_eq!(err.code(), None);
+        assert_eq!(err.to_string(), "http error [502] Bad Gateway");
+    }
+}
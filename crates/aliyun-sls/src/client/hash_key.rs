@@ -0,0 +1,122 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::{fmt, str::FromStr};
+
+/// Hash key routing logs to the shard whose hash range contains it.
+///
+/// A hash key is a 128-bit integer, formatted as 32 lowercase hex digits.
+///
+/// ```
+/// use aliyun_sls::HashKey;
+///
+/// let key: HashKey = "7f000000000000000000000000000000".parse().unwrap();
+/// assert_eq!(key, HashKey::new(0x7f << 120));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct HashKey(u128);
+
+/// Error parsing a [`HashKey`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("hash key must be 32 hex digits")]
+pub struct ParseHashKeyError;
+
+impl HashKey {
+    /// Create a hash key from its integer value.
+    pub const fn new(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Derive a hash key from any data, e.g. a tenant id, using BLAKE3.
+    ///
+    /// The same data always routes to the same shard, as long as the shards are not split or merged.
+    #[cfg(feature = "derive-key")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "derive-key")))]
+    pub fn derive(data: impl AsRef<[u8]>) -> Self {
+        let hash = blake3::hash(data.as_ref());
+        let (bytes, _) = hash.as_bytes().split_first_chunk::<16>().expect("32 bytes");
+        Self(u128::from_be_bytes(*bytes))
+    }
+
+    /// Integer value of the hash key.
+    pub const fn value(&self) -> u128 {
+        self.0
+    }
+}
+
+impl From<u128> for HashKey {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for HashKey {
+    type Err = ParseHashKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 32 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseHashKeyError);
+        }
+        u128::from_str_radix(s, 16)
+            .map(Self)
+            .map_err(|_| ParseHashKeyError)
+    }
+}
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+impl Serialize for HashKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HashKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_key() {
+        let key = HashKey::new(1);
+        assert_eq!(key.to_string(), "00000000000000000000000000000001");
+        assert_eq!(
+            "00000000000000000000000000000001"
+                .parse::<HashKey>()
+                .unwrap(),
+            key
+        );
+        assert_eq!(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"
+                .parse::<HashKey>()
+                .unwrap(),
+            HashKey::new(u128::MAX)
+        );
+        assert!("1".parse::<HashKey>().is_err());
+        assert!(
+            "+0000000000000000000000000000001"
+                .parse::<HashKey>()
+                .is_err()
+        );
+        assert!("shard_key".parse::<HashKey>().is_err());
+
+        #[cfg(feature = "derive-key")]
+        {
+            assert_eq!(HashKey::derive("tenant-1"), HashKey::derive("tenant-1"));
+            assert_ne!(HashKey::derive("tenant-1"), HashKey::derive("tenant-2"));
+            // First 16 bytes of BLAKE3("abc")
+            assert_eq!(
+                HashKey::derive("abc").to_string(),
+                "6437b3ac38465133ffb63b75273a8db5"
+            );
+        }
+    }
+}
@@ -1,15 +1,29 @@
 #![allow(dead_code)]
+pub const ACCEPT: &str = "accept";
+pub const ACCEPT_ENCODING: &str = "accept-encoding";
 pub const AUTHORIZATION: &str = "authorization";
 pub const CONTENT_LENGTH: &str = "content-length";
 pub const CONTENT_TYPE: &str = "content-type";
 pub const DATE: &str = "date";
+pub const HOST: &str = "host";
 pub const API_VERSION: &str = "0.6.0";
 pub const LOG_API_VERSION: &str = "x-log-apiversion";
 pub const LOG_SIGNATURE_METHOD: &str = "x-log-signaturemethod";
 pub const LOG_BODY_RAW_SIZE: &str = "x-log-bodyrawsize";
 pub const LOG_COMPRESS_TYPE: &str = "x-log-compresstype";
+pub const LOG_DATE: &str = "x-log-date";
+pub const LOG_CONTENT_SHA256: &str = "x-log-content-sha256";
+pub const LOG_REQUEST_ID: &str = "x-log-requestid";
+pub const LOG_PROGRESS: &str = "x-log-progress";
+pub const LOG_COUNT: &str = "x-log-count";
+pub const LOG_PROCESSED_ROWS: &str = "x-log-processed-rows";
+pub const LOG_ELAPSED_MILLISECOND: &str = "x-log-elapsed-millisecond";
+pub const LOG_HAS_SQL: &str = "x-log-has-sql";
+pub const LOG_CURSOR: &str = "x-log-cursor";
+pub const ACS_SECURITY_TOKEN: &str = "x-acs-security-token";
 
 pub const CONTENT_MD5: &str = "content-md5";
 pub const USER_AGENT_VALUE: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 pub const DEFAULT_CONTENT_TYPE: &str = "application/x-protobuf";
+pub const JSON_CONTENT_TYPE: &str = "application/json";
 pub const SIGNATURE_METHOD: &str = "hmac-sha1";
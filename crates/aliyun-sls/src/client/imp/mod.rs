@@ -1,29 +1,36 @@
-use std::fmt;
+use crate::{
+    client::SlsClientBuilderError,
+    transport::{HttpBackend, HttpConfig, HttpTransport},
+};
+use std::sync::Arc;
+
+#[cfg(not(any(feature = "reqwest", feature = "nyquest")))]
+compile_error!("at least one of the `reqwest` and `nyquest` features must be enabled");
 
 #[cfg(feature = "nyquest")]
 mod nyquest;
-#[cfg(feature = "nyquest")]
-pub(crate) use nyquest::*;
 
 #[cfg(feature = "reqwest")]
 mod reqwest;
-#[cfg(feature = "reqwest")]
-pub(crate) use reqwest::*;
 
-impl Response {
-    pub fn status(&self) -> StatusCode {
-        StatusCode {
-            inner: self.inner.status(),
-        }
-    }
-
-    pub async fn text(self) -> Result<String, Error> {
-        self.inner.text().await
-    }
-}
+#[cfg(feature = "blocking")]
+pub(crate) use reqwest::ReqwestBlockingTransport;
 
-impl fmt::Display for StatusCode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.inner)
-    }
+/// Create the transport of a built-in backend.
+pub(crate) fn transport(
+    backend: HttpBackend,
+    config: HttpConfig,
+) -> Result<Arc<dyn HttpTransport>, SlsClientBuilderError> {
+    Ok(match backend {
+        #[cfg(feature = "reqwest")]
+        HttpBackend::Reqwest => Arc::new(
+            reqwest::ReqwestTransport::new(config)
+                .map_err(SlsClientBuilderError::InvalidHttpConfig)?,
+        ),
+        #[cfg(feature = "nyquest")]
+        HttpBackend::Nyquest => Arc::new(
+            nyquest::NyquestTransport::new(config)
+                .map_err(SlsClientBuilderError::UnsupportedHttpConfig)?,
+        ),
+    })
 }
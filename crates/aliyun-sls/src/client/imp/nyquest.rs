@@ -1,91 +1,247 @@
-use crate::client::headers;
+use crate::{
+    client::headers,
+    transport::{
+        HttpConfig, HttpRequest, HttpResponse, HttpTransport, Method, TransportError,
+        TransportFuture,
+    },
+};
 use async_lock::OnceCell;
-use std::borrow::Cow;
+use std::{
+    collections::BTreeMap,
+    future::poll_fn,
+    io,
+    pin::pin,
+    sync::{
+        Condvar, LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
 
-static HTTP_CLIENT: OnceCell<HttpClient> = OnceCell::new();
+/// nyquest cannot list the response headers, only those used by the client are kept.
+const RESPONSE_HEADERS: &[&str] = &[
+    headers::LOG_REQUEST_ID,
+    headers::LOG_PROGRESS,
+    headers::LOG_COUNT,
+    headers::LOG_PROCESSED_ROWS,
+    headers::LOG_ELAPSED_MILLISECOND,
+    headers::LOG_HAS_SQL,
+    headers::LOG_CURSOR,
+    headers::LOG_BODY_RAW_SIZE,
+    headers::LOG_COMPRESS_TYPE,
+];
 
-#[derive(Clone)]
-pub(crate) struct HttpClient {
-    inner: nyquest::AsyncClient,
+/// [`HttpTransport`] backed by [`nyquest`].
+pub(crate) struct NyquestTransport {
+    config: HttpConfig,
+    client: OnceCell<nyquest::AsyncClient>,
 }
 
-#[must_use = "RequestBuilder does nothing until you 'send' it"]
-pub(crate) struct RequestBuilder {
-    client: HttpClient,
-    inner: nyquest::r#async::Request,
-}
-
-pub(crate) struct Response {
-    pub(crate) inner: nyquest::r#async::Response,
-}
+type Result<T, E = TransportError> = std::result::Result<T, E>;
 
-pub(crate) struct StatusCode {
-    pub(crate) inner: nyquest::StatusCode,
-}
-
-pub type Error = nyquest::Error;
-type Result<T, E = Error> = std::result::Result<T, E>;
-
-impl HttpClient {
-    async fn new() -> Result<Self> {
+impl NyquestTransport {
+    /// Fails with the name of the first option of `config` not supported by nyquest.
+    pub(crate) fn new(config: HttpConfig) -> Result<Self, &'static str> {
+        let HttpConfig {
+            connect_timeout,
+            timeout: _,
+            proxy,
+            pool_idle_timeout,
+            pool_max_idle_per_host,
+            root_certificates,
+            tcp_keepalive,
+        } = &config;
+        let unsupported = [
+            ("connect_timeout", connect_timeout.is_some()),
+            ("proxy", proxy.is_some()),
+            ("pool_idle_timeout", pool_idle_timeout.is_some()),
+            ("pool_max_idle_per_host", pool_max_idle_per_host.is_some()),
+            ("root_certificates", !root_certificates.is_empty()),
+            ("tcp_keepalive", tcp_keepalive.is_some()),
+        ];
+        if let Some((name, _)) = unsupported.into_iter().find(|&(_, set)| set) {
+            return Err(name);
+        }
         Ok(Self {
-            inner: nyquest::ClientBuilder::default()
-                .user_agent(headers::USER_AGENT_VALUE)
-                // .with_header(headers::CONTENT_TYPE, headers::DEFAULT_CONTENT_TYPE)
-                .with_header(headers::LOG_API_VERSION, headers::API_VERSION)
-                .with_header(headers::LOG_SIGNATURE_METHOD, headers::SIGNATURE_METHOD)
-                .build_async()
-                .await?,
+            config,
+            client: OnceCell::new(),
         })
     }
 
-    pub async fn get_or_try_init() -> Result<&'static Self> {
-        HTTP_CLIENT.get_or_try_init(HttpClient::new).await
+    async fn client(&self) -> Result<&nyquest::AsyncClient> {
+        self.client
+            .get_or_try_init(|| {
+                let mut builder =
+                    nyquest::ClientBuilder::default().user_agent(headers::USER_AGENT_VALUE);
+                if let Some(timeout) = self.config.timeout {
+                    builder = builder.request_timeout(timeout);
+                }
+                builder.build_async()
+            })
+            .await
+            .map_err(into_transport_error)
     }
 
-    pub fn post(&self, url: &str) -> RequestBuilder {
-        RequestBuilder {
-            client: self.clone(),
-            inner: nyquest::Request::post(url.to_string()),
+    async fn send_inner(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let client = self.client().await?;
+        let read_body = request.read_body;
+        let mut inner = match request.method {
+            Method::Get => nyquest::Request::get(request.url),
+            Method::Post => nyquest::Request::post(request.url),
+            Method::Put => nyquest::Request::put(request.url),
+            Method::Delete => nyquest::Request::delete(request.url),
+        };
+        let mut content_type = None;
+        for (name, value) in request.headers {
+            if name == headers::CONTENT_TYPE {
+                // nyquest sets the content type along with the body
+                content_type = Some(value);
+            } else {
+                inner = inner.with_header(name, value);
+            }
         }
+        if !request.body.is_empty() {
+            let content_type = content_type.unwrap_or_else(|| "application/octet-stream".into());
+            inner = inner.with_body(nyquest::Body::bytes(request.body, content_type));
+        }
+
+        let res = client.request(inner).await.map_err(into_transport_error)?;
+        let status = res.status().code();
+        let headers = RESPONSE_HEADERS
+            .iter()
+            .filter_map(|&name| {
+                let value = res.get_header(name).ok()?.into_iter().next()?;
+                Some((name.to_string(), value))
+            })
+            .collect();
+        let body = match read_body || !(200..300).contains(&status) {
+            true => res.bytes().await.map_err(into_transport_error)?,
+            false => Vec::new(),
+        };
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
     }
 }
 
-impl RequestBuilder {
-    pub fn header(
-        self,
-        key: impl Into<Cow<'static, str>>,
-        value: impl Into<Cow<'static, str>>,
-    ) -> RequestBuilder {
-        RequestBuilder {
-            client: self.client,
-            inner: self.inner.with_header(key, value),
+impl HttpTransport for NyquestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        // nyquest only supports a client wide request timeout
+        match request.timeout {
+            Some(timeout) => Box::pin(with_timeout(self.send_inner(request), timeout)),
+            None => Box::pin(self.send_inner(request)),
         }
     }
+}
+
+/// Timer thread shared by the requests with a timeout.
+#[derive(Default)]
+struct Timers {
+    /// Wakers of the pending requests, by deadline and id.
+    wakers: Mutex<BTreeMap<(Instant, u64), Waker>>,
+    /// Notified when an earlier deadline is registered.
+    changed: Condvar,
+}
 
-    pub fn body(self, body: Vec<u8>) -> RequestBuilder {
-        RequestBuilder {
-            client: self.client,
-            inner: self
-                .inner
-                .with_body(nyquest::Body::bytes(body, headers::DEFAULT_CONTENT_TYPE)),
+impl Timers {
+    /// The shared timers, the thread is spawned on first use.
+    fn get() -> &'static Self {
+        static TIMERS: LazyLock<Timers> = LazyLock::new(|| {
+            thread::Builder::new()
+                .name("aliyun-sls-timer".to_string())
+                .spawn(|| Timers::get().run())
+                .expect("failed to spawn the timer thread");
+            Timers::default()
+        });
+        &TIMERS
+    }
+
+    /// Wake the requests as their deadline passes.
+    fn run(&self) {
+        let mut wakers = self.wakers.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = wakers.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                entry.remove().wake();
+            }
+            wakers = match wakers.keys().next() {
+                Some(&(deadline, _)) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    self.changed.wait_timeout(wakers, timeout).unwrap().0
+                }
+                None => self.changed.wait(wakers).unwrap(),
+            };
+        }
+    }
+
+    fn register(&self, key: (Instant, u64), waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        let earliest = wakers.keys().next().is_none_or(|first| key < *first);
+        wakers.insert(key, waker.clone());
+        if earliest {
+            self.changed.notify_one();
         }
     }
 
-    pub async fn send(self) -> Result<Response> {
-        let res = self.client.inner.request(self.inner).await?;
-        Ok(Response { inner: res })
+    fn cancel(&self, key: &(Instant, u64)) {
+        self.wakers.lock().unwrap().remove(key);
     }
 }
 
-impl StatusCode {
-    pub(crate) fn is_success(&self) -> bool {
-        self.inner.is_successful()
+/// Fail with [`TransportError::Timeout`] if `fut` does not complete within `timeout`.
+async fn with_timeout<T>(fut: impl Future<Output = Result<T>>, timeout: Duration) -> Result<T> {
+    /// Unregister the request from the timers once it completes or is dropped.
+    struct Cancel((Instant, u64));
+
+    impl Drop for Cancel {
+        fn drop(&mut self) {
+            Timers::get().cancel(&self.0);
+        }
     }
+
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        return fut.await;
+    };
+    let key = (deadline, NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let _cancel = Cancel(key);
+
+    let mut fut = pin!(fut);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = fut.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+        if Instant::now() >= deadline {
+            return Poll::Ready(Err(TransportError::timeout(format!("{timeout:?} elapsed"))));
+        }
+        Timers::get().register(key, cx.waker());
+        Poll::Pending
+    })
+    .await
 }
 
-impl From<StatusCode> for u16 {
-    fn from(status: StatusCode) -> u16 {
-        status.inner.code()
+fn into_transport_error(err: nyquest::Error) -> TransportError {
+    match err {
+        nyquest::Error::RequestTimeout => TransportError::timeout(err),
+        // Only errors before sending are safe to retry, others may come after the request is sent
+        nyquest::Error::Io(ref e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::AddrNotAvailable
+                    | io::ErrorKind::HostUnreachable
+                    | io::ErrorKind::NetworkUnreachable
+            ) =>
+        {
+            TransportError::connect(err)
+        }
+        err => TransportError::other(err),
     }
 }
@@ -1,100 +1,192 @@
-use crate::client::headers;
-use async_lock::OnceCell;
-use http::HeaderMap;
-use reqwest::header::{HeaderName, HeaderValue};
+use crate::{
+    client::headers,
+    transport::{
+        HttpConfig, HttpRequest, HttpResponse, HttpTransport, Method, TransportError,
+        TransportFuture,
+    },
+};
 
-static HTTP_CLIENT: OnceCell<HttpClient> = OnceCell::new();
-
-#[derive(Clone)]
-pub(crate) struct HttpClient {
-    inner: reqwest::Client,
+/// Apply the [`HttpConfig`] to a async or blocking `ClientBuilder`.
+macro_rules! configure {
+    ($builder:expr, $config:expr) => {{
+        let config: &HttpConfig = $config;
+        let mut builder = $builder.user_agent(headers::USER_AGENT_VALUE);
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(TransportError::other)?);
+        }
+        if let Some(pool_idle_timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        if let Some(tcp_keepalive) = config.tcp_keepalive {
+            builder = builder.tcp_keepalive(tcp_keepalive);
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(any(feature = "reqwest-default-tls", feature = "reqwest-rustls"))] {
+                for pem in &config.root_certificates {
+                    let certificate =
+                        reqwest::Certificate::from_pem(pem).map_err(TransportError::other)?;
+                    builder = builder.add_root_certificate(certificate);
+                }
+            } else {
+                if !config.root_certificates.is_empty() {
+                    return Err(TransportError::other(
+                        "root certificates require a reqwest tls feature",
+                    ));
+                }
+            }
+        }
+        builder
+    }};
 }
 
-#[must_use = "RequestBuilder does nothing until you 'send' it"]
-pub(crate) struct RequestBuilder {
-    inner: reqwest::RequestBuilder,
+/// Create a async or blocking `RequestBuilder` for the [`HttpRequest`].
+macro_rules! request_builder {
+    ($client:expr, $request:expr) => {{
+        let request: HttpRequest = $request;
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+        };
+        let mut builder = $client.request(method, request.url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if !request.body.is_empty() {
+            builder = builder.body(request.body);
+        }
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder
+    }};
 }
 
-pub(crate) struct Response {
-    pub(crate) inner: reqwest::Response,
+/// [`HttpTransport`] backed by [`reqwest`].
+#[derive(Debug)]
+pub(crate) struct ReqwestTransport {
+    client: reqwest::Client,
 }
 
-pub(crate) struct StatusCode {
-    pub(crate) inner: http::StatusCode,
-}
+type Result<T, E = TransportError> = std::result::Result<T, E>;
 
-pub type Error = reqwest::Error;
-type Result<T, E = Error> = std::result::Result<T, E>;
+impl ReqwestTransport {
+    /// Build the client, failing if the config is invalid, e.g. a malformed proxy url.
+    pub(crate) fn new(config: HttpConfig) -> Result<Self> {
+        let client = configure!(reqwest::ClientBuilder::new(), &config)
+            .build()
+            .map_err(TransportError::other)?;
+        Ok(Self { client })
+    }
 
-impl HttpClient {
-    async fn new() -> Result<Self> {
-        Ok(Self {
-            inner: reqwest::ClientBuilder::new()
-                .user_agent(headers::USER_AGENT_VALUE)
-                .https_only(true)
-                .default_headers(HeaderMap::from_iter([
-                    (
-                        HeaderName::from_static(headers::CONTENT_TYPE),
-                        HeaderValue::from_static(headers::DEFAULT_CONTENT_TYPE),
-                    ),
-                    (
-                        HeaderName::from_static(headers::LOG_API_VERSION),
-                        HeaderValue::from_static(headers::API_VERSION),
-                    ),
-                    (
-                        HeaderName::from_static(headers::LOG_SIGNATURE_METHOD),
-                        HeaderValue::from_static(headers::SIGNATURE_METHOD),
-                    ),
-                ]))
-                .build()?,
+    async fn send_inner(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let read_body = request.read_body;
+        let builder = request_builder!(self.client, request);
+        let res = builder.send().await.map_err(into_transport_error)?;
+        let (status, headers) = (res.status().as_u16(), response_headers(res.headers()));
+        let body = match read_body || !res.status().is_success() {
+            true => res.bytes().await.map_err(into_transport_error)?.to_vec(),
+            false => Vec::new(),
+        };
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
         })
     }
+}
 
-    pub async fn get_or_try_init() -> Result<&'static Self> {
-        HTTP_CLIENT.get_or_try_init(HttpClient::new).await
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(self.send_inner(request))
     }
+}
 
-    pub fn post(&self, url: &str) -> RequestBuilder {
-        RequestBuilder {
-            inner: self.inner.post(url),
-        }
-    }
+/// [`HttpTransport`] backed by [`reqwest::blocking`].
+///
+/// The request is sent synchronously when the returned future is first polled.
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub(crate) struct ReqwestBlockingTransport {
+    config: HttpConfig,
+    client: std::sync::OnceLock<reqwest::blocking::Client>,
 }
 
-impl RequestBuilder {
-    pub fn header<K, V>(self, key: K, value: V) -> RequestBuilder
-    where
-        HeaderName: TryFrom<K>,
-        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
-        HeaderValue: TryFrom<V>,
-        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
-    {
-        RequestBuilder {
-            inner: self.inner.header(key, value),
-        }
+#[cfg(feature = "blocking")]
+impl ReqwestBlockingTransport {
+    /// Create the transport, failing if the config is invalid, e.g. a malformed proxy url.
+    ///
+    /// The blocking client can't be built within an async runtime, so it is built on first use,
+    /// the config is validated with the async builder instead.
+    pub(crate) fn new(config: HttpConfig) -> Result<Self> {
+        configure!(reqwest::ClientBuilder::new(), &config)
+            .build()
+            .map_err(TransportError::other)?;
+        Ok(Self {
+            config,
+            client: std::sync::OnceLock::new(),
+        })
     }
 
-    pub fn body(self, body: Vec<u8>) -> RequestBuilder {
-        RequestBuilder {
-            inner: self.inner.body(body),
+    fn client(&self) -> Result<&reqwest::blocking::Client> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
         }
+        let client = configure!(reqwest::blocking::ClientBuilder::new(), &self.config)
+            .build()
+            .map_err(TransportError::other)?;
+        Ok(self.client.get_or_init(|| client))
     }
 
-    pub async fn send(self) -> Result<Response> {
-        Ok(Response {
-            inner: self.inner.send().await?,
+    fn send_blocking(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let client = self.client()?;
+        let read_body = request.read_body;
+        let res = request_builder!(client, request)
+            .send()
+            .map_err(into_transport_error)?;
+        let (status, headers) = (res.status().as_u16(), response_headers(res.headers()));
+        let body = match read_body || !res.status().is_success() {
+            true => res.bytes().map_err(into_transport_error)?.to_vec(),
+            false => Vec::new(),
+        };
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
         })
     }
 }
 
-impl StatusCode {
-    pub(crate) fn is_success(&self) -> bool {
-        self.inner.is_success()
+#[cfg(feature = "blocking")]
+impl HttpTransport for ReqwestBlockingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move { self.send_blocking(request) })
     }
 }
 
-impl From<StatusCode> for u16 {
-    fn from(status: StatusCode) -> u16 {
-        status.inner.as_u16()
+fn response_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn into_transport_error(err: reqwest::Error) -> TransportError {
+    if err.is_timeout() {
+        TransportError::timeout(err)
+    } else if err.is_connect() {
+        TransportError::connect(err)
+    } else {
+        TransportError::other(err)
     }
 }
@@ -0,0 +1,299 @@
+use crate::{
+    client::{SlsClient, SlsClientError, Target, parse_json, validate_target},
+    transport::{HttpResponse, Method},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default delimiters splitting text into tokens.
+const DEFAULT_TOKENS: [&str; 22] = [
+    ",", " ", "'", "\"", ";", "=", "(", ")", "[", "]", "{", "}", "?", "@", "&", "<", ">", "/", ":",
+    "\n", "\t", "\r",
+];
+
+/// Index configuration of a logstore, required to query its logs.
+///
+/// See <https://help.aliyun.com/zh/sls/developer-reference/api-sls-2020-12-30-createindex>.
+///
+/// ```
+/// use aliyun_sls::{FieldIndex, FullTextIndex, Index};
+///
+/// let index = Index::new()
+///     .with_full_text(FullTextIndex::new())
+///     .with_field("level", FieldIndex::text().with_analytics(true))
+///     .with_field("latency", FieldIndex::long());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<FullTextIndex>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    keys: BTreeMap<String, FieldIndex>,
+}
+
+/// Full text index, searching tokens in the whole log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullTextIndex {
+    token: Vec<String>,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    chn: bool,
+}
+
+/// Index of the values of a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldIndex {
+    #[serde(rename = "type")]
+    field_type: FieldType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    token: Vec<String>,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    chn: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    alias: String,
+    #[serde(default, rename = "doc_value")]
+    analytics: bool,
+}
+
+/// Type of an indexed field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum FieldType {
+    /// Text split into tokens.
+    Text,
+    /// 64-bit integer.
+    Long,
+    /// 64-bit floating point number.
+    Double,
+    /// JSON value.
+    Json,
+}
+
+impl Index {
+    /// Create an empty index configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the full text index.
+    pub fn with_full_text(mut self, full_text: FullTextIndex) -> Self {
+        self.line = Some(full_text);
+        self
+    }
+
+    /// Add the index of a field.
+    pub fn with_field(mut self, name: impl Into<String>, field: FieldIndex) -> Self {
+        self.keys.insert(name.into(), field);
+        self
+    }
+
+    /// Full text index, if enabled.
+    pub fn full_text(&self) -> Option<&FullTextIndex> {
+        self.line.as_ref()
+    }
+
+    /// Indexes of fields, by field name.
+    pub fn fields(&self) -> &BTreeMap<String, FieldIndex> {
+        &self.keys
+    }
+}
+
+impl FullTextIndex {
+    /// Create a case-insensitive full text index with the default delimiters.
+    pub fn new() -> Self {
+        Self {
+            token: default_tokens(),
+            case_sensitive: false,
+            chn: false,
+        }
+    }
+
+    /// Set the delimiters splitting text into tokens.
+    pub fn with_tokens<I: IntoIterator<Item = S>, S: Into<String>>(mut self, tokens: I) -> Self {
+        self.token = tokens.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Make searches case-sensitive.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Split Chinese text into words.
+    pub fn with_chinese(mut self, chinese: bool) -> Self {
+        self.chn = chinese;
+        self
+    }
+}
+
+impl Default for FullTextIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FieldIndex {
+    /// Create the index of a field of the given type.
+    ///
+    /// Text fields are split with the default delimiters.
+    pub fn new(field_type: FieldType) -> Self {
+        Self {
+            field_type,
+            token: match field_type {
+                FieldType::Text | FieldType::Json => default_tokens(),
+                _ => Vec::new(),
+            },
+            case_sensitive: false,
+            chn: false,
+            alias: String::new(),
+            analytics: false,
+        }
+    }
+
+    /// Index of a text field.
+    pub fn text() -> Self {
+        Self::new(FieldType::Text)
+    }
+
+    /// Index of an integer field.
+    pub fn long() -> Self {
+        Self::new(FieldType::Long)
+    }
+
+    /// Index of a floating point field.
+    pub fn double() -> Self {
+        Self::new(FieldType::Double)
+    }
+
+    /// Set the delimiters splitting text into tokens.
+    pub fn with_tokens<I: IntoIterator<Item = S>, S: Into<String>>(mut self, tokens: I) -> Self {
+        self.token = tokens.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Make searches case-sensitive.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Split Chinese text into words.
+    pub fn with_chinese(mut self, chinese: bool) -> Self {
+        self.chn = chinese;
+        self
+    }
+
+    /// Set the name of the field in analytic statements.
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = alias.into();
+        self
+    }
+
+    /// Enable analytic statements on the field.
+    pub fn with_analytics(mut self, analytics: bool) -> Self {
+        self.analytics = analytics;
+        self
+    }
+
+    /// Type of the field.
+    pub fn field_type(&self) -> FieldType {
+        self.field_type
+    }
+
+    /// Whether analytic statements are enabled on the field.
+    pub fn analytics(&self) -> bool {
+        self.analytics
+    }
+}
+
+fn default_tokens() -> Vec<String> {
+    DEFAULT_TOKENS.iter().map(|&token| token.into()).collect()
+}
+
+impl SlsClient {
+    /// Create the index of the logstore of the given target.
+    ///
+    /// Fails with [`ErrorCode::IndexAlreadyExist`](crate::ErrorCode::IndexAlreadyExist)
+    /// if it already exists.
+    pub async fn create_index(&self, target: &Target, index: &Index) -> Result<(), SlsClientError> {
+        self.index_request("create_index", Method::Post, target, Some(index))
+            .await
+            .map(drop)
+    }
+
+    /// Get the index of the logstore of the given target.
+    ///
+    /// Fails with [`ErrorCode::IndexConfigNotExist`](crate::ErrorCode::IndexConfigNotExist)
+    /// if it has none.
+    pub async fn get_index(&self, target: &Target) -> Result<Index, SlsClientError> {
+        let res = self
+            .index_request("get_index", Method::Get, target, None)
+            .await?;
+        parse_json(&res)
+    }
+
+    /// Replace the index of the logstore of the given target.
+    ///
+    /// Only logs written afterwards are indexed with the new configuration.
+    pub async fn update_index(&self, target: &Target, index: &Index) -> Result<(), SlsClientError> {
+        self.index_request("update_index", Method::Put, target, Some(index))
+            .await
+            .map(drop)
+    }
+
+    async fn index_request(
+        &self,
+        operation: &str,
+        method: Method,
+        target: &Target,
+        index: Option<&Index>,
+    ) -> Result<HttpResponse, SlsClientError> {
+        validate_target(target)?;
+        let resource = format!("/logstores/{}/index", target.logstore());
+        let body = index.map(|index| serde_json::to_value(index).expect("infallible"));
+        self.send_json(
+            operation,
+            method,
+            method.is_idempotent(),
+            target.project(),
+            &resource,
+            &BTreeMap::new(),
+            body,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FieldIndex, FullTextIndex, Index};
+
+    #[test]
+    fn test_index() {
+        let index = Index::new()
+            .with_full_text(FullTextIndex::new().with_tokens([",", " "]))
+            .with_field("latency", FieldIndex::long().with_analytics(true));
+        let json = serde_json::to_string(&index).unwrap();
+        assert_eq!(
+            json,
+            r#"{"line":{"token":[","," "],"caseSensitive":false,"chn":false},"keys":{"latency":{"type":"long","caseSensitive":false,"chn":false,"doc_value":true}}}"#
+        );
+
+        let index: Index = serde_json::from_str(
+            r#"{"line":{"token":[","],"caseSensitive":true},"keys":{"level":{"type":"text","token":[","],"doc_value":false}},"ttl":30,"lastModifyTime":1700000000}"#,
+        )
+        .unwrap();
+        assert!(index.full_text().is_some());
+        assert_eq!(
+            index.fields()["level"],
+            FieldIndex::text().with_tokens([","])
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{
+    client::{
+        HashKey, Shard, SlsClient, SlsClientError, Target, parse_json, validate_project,
+        validate_target,
+    },
+    transport::Method,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Page size used to list logstores.
+const LIST_PAGE_SIZE: u32 = 500;
+
+/// Logstore configuration.
+///
+/// See <https://help.aliyun.com/zh/sls/developer-reference/api-sls-2020-12-30-createlogstore>.
+///
+/// ```
+/// use aliyun_sls::LogStore;
+///
+/// let logstore = LogStore::new("access-log", 30).with_shard_count(4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStore {
+    #[serde(rename = "logstoreName")]
+    name: String,
+    ttl: u32,
+    shard_count: u32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    auto_split: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_split_shard: Option<u32>,
+    #[serde(default)]
+    append_meta: bool,
+}
+
+impl LogStore {
+    /// Create a logstore configuration keeping logs for `ttl` days, with 2 shards.
+    ///
+    /// A `ttl` of `3650` keeps logs forever.
+    pub fn new(name: impl Into<String>, ttl: u32) -> Self {
+        Self {
+            name: name.into(),
+            ttl,
+            shard_count: 2,
+            auto_split: false,
+            max_split_shard: None,
+            append_meta: false,
+        }
+    }
+
+    /// Set the number of shards, only used when creating the logstore.
+    pub fn with_shard_count(mut self, shard_count: u32) -> Self {
+        self.shard_count = shard_count;
+        self
+    }
+
+    /// Split shards automatically when their write quota is exceeded,
+    /// up to `max_split_shard` shards.
+    ///
+    /// `0` disables automatic splitting, which is the default,
+    /// and leaves the auto split settings out of the request.
+    pub fn with_auto_split(mut self, max_split_shard: u32) -> Self {
+        self.auto_split = max_split_shard > 0;
+        self.max_split_shard = self.auto_split.then_some(max_split_shard);
+        self
+    }
+
+    /// Record the public IP and receive time of logs in their tags.
+    pub fn with_append_meta(mut self, append_meta: bool) -> Self {
+        self.append_meta = append_meta;
+        self
+    }
+
+    /// Name of the logstore.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of days logs are kept.
+    pub fn ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    /// Number of shards.
+    pub fn shard_count(&self) -> u32 {
+        self.shard_count
+    }
+
+    /// Maximum number of shards after automatic splitting, `None` if disabled.
+    pub fn auto_split(&self) -> Option<u32> {
+        match self.auto_split {
+            true => Some(self.max_split_shard.unwrap_or_default()),
+            false => None,
+        }
+    }
+
+    /// Whether the public IP and receive time of logs are recorded.
+    pub fn append_meta(&self) -> bool {
+        self.append_meta
+    }
+}
+
+impl SlsClient {
+    /// Create a logstore in `project`.
+    ///
+    /// Fails with [`ErrorCode::LogStoreAlreadyExist`](crate::ErrorCode::LogStoreAlreadyExist)
+    /// if it already exists.
+    pub async fn create_logstore(
+        &self,
+        project: &str,
+        logstore: &LogStore,
+    ) -> Result<(), SlsClientError> {
+        validate_target(&Target::new(project, &logstore.name))?;
+        self.send_json(
+            "create_logstore",
+            Method::Post,
+            false,
+            project,
+            "/logstores",
+            &BTreeMap::new(),
+            Some(serde_json::to_value(logstore).expect("infallible")),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Get the configuration of a logstore of `project`.
+    pub async fn get_logstore(
+        &self,
+        project: &str,
+        name: &str,
+    ) -> Result<LogStore, SlsClientError> {
+        validate_target(&Target::new(project, name))?;
+        let res = self
+            .send_json(
+                "get_logstore",
+                Method::Get,
+                true,
+                project,
+                &format!("/logstores/{name}"),
+                &BTreeMap::new(),
+                None,
+            )
+            .await?;
+        parse_json(&res)
+    }
+
+    /// Update the configuration of a logstore of `project`, except its shard count.
+    pub async fn update_logstore(
+        &self,
+        project: &str,
+        logstore: &LogStore,
+    ) -> Result<(), SlsClientError> {
+        validate_target(&Target::new(project, &logstore.name))?;
+        self.send_json(
+            "update_logstore",
+            Method::Put,
+            true,
+            project,
+            &format!("/logstores/{}", logstore.name),
+            &BTreeMap::new(),
+            Some(serde_json::to_value(logstore).expect("infallible")),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Delete a logstore of `project`, and all its logs.
+    pub async fn delete_logstore(&self, project: &str, name: &str) -> Result<(), SlsClientError> {
+        validate_target(&Target::new(project, name))?;
+        self.send_json(
+            "delete_logstore",
+            Method::Delete,
+            false,
+            project,
+            &format!("/logstores/{name}"),
+            &BTreeMap::new(),
+            None,
+        )
+        .await
+        .map(drop)
+    }
+
+    /// List the names of all logstores of `project`.
+    pub async fn list_logstores(&self, project: &str) -> Result<Vec<String>, SlsClientError> {
+        #[derive(Deserialize)]
+        struct Body {
+            total: u32,
+            logstores: Vec<String>,
+        }
+
+        validate_project(project)?;
+        let mut logstores = Vec::new();
+        loop {
+            let query = BTreeMap::from([
+                ("offset".to_string(), logstores.len().to_string()),
+                ("size".to_string(), LIST_PAGE_SIZE.to_string()),
+            ]);
+            let res = self
+                .send_json(
+                    "list_logstores",
+                    Method::Get,
+                    true,
+                    project,
+                    "/logstores",
+                    &query,
+                    None,
+                )
+                .await?;
+            let body: Body = parse_json(&res)?;
+            let last_page = body.logstores.is_empty();
+            logstores.extend(body.logstores);
+            if last_page || logstores.len() >= body.total as usize {
+                return Ok(logstores);
+            }
+        }
+    }
+
+    /// Split a readwrite shard at `split_key`, which must be in its hash range.
+    ///
+    /// The shards are listed first, and the request fails with
+    /// [`SlsClientError::InvalidArgument`] if `split_key` is not strictly inside
+    /// the hash range of the shard.
+    ///
+    /// Returns the shard, now readonly, and the two new shards.
+    pub async fn split_shard(
+        &self,
+        target: &Target,
+        shard: u32,
+        split_key: HashKey,
+    ) -> Result<Vec<Shard>, SlsClientError> {
+        let shards = self.list_shards(target).await?;
+        // A missing shard is left to the service, which reports it with its error code
+        if let Some(range) = shards.iter().find(|s| s.id == shard) {
+            if split_key <= range.begin_key || split_key >= range.end_key {
+                return Err(SlsClientError::InvalidArgument(
+                    format!(
+                        "split key {split_key} is not inside the hash range {}..{} of shard {shard}",
+                        range.begin_key, range.end_key
+                    )
+                    .into(),
+                ));
+            }
+        }
+        let query = BTreeMap::from([
+            ("action".to_string(), "split".to_string()),
+            ("key".to_string(), split_key.to_string()),
+        ]);
+        self.shard_action("split_shard", target, shard, query).await
+    }
+
+    /// Merge a readwrite shard with the next adjacent one.
+    ///
+    /// Returns the two shards, now readonly, and the new shard.
+    pub async fn merge_shards(
+        &self,
+        target: &Target,
+        shard: u32,
+    ) -> Result<Vec<Shard>, SlsClientError> {
+        let query = BTreeMap::from([("action".to_string(), "merge".to_string())]);
+        self.shard_action("merge_shards", target, shard, query)
+            .await
+    }
+
+    async fn shard_action(
+        &self,
+        operation: &str,
+        target: &Target,
+        shard: u32,
+        query: BTreeMap<String, String>,
+    ) -> Result<Vec<Shard>, SlsClientError> {
+        validate_target(target)?;
+        let resource = format!("/logstores/{}/shards/{shard}", target.logstore());
+        let res = self
+            .send_json(
+                operation,
+                Method::Post,
+                false,
+                target.project(),
+                &resource,
+                &query,
+                None,
+            )
+            .await?;
+        parse_json(&res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        HashKey, LogStore, RetryPolicy, SlsClientError, Target,
+        test_utils::{FakeTransport, test_client},
+        transport::{HttpResponse, Method},
+    };
+
+    #[tokio::test]
+    async fn test_logstore() {
+        let transport = FakeTransport::new(|request| {
+            let path = request
+                .url
+                .strip_prefix("https://playground.cn-guangzhou.log.aliyuncs.com")
+                .unwrap();
+            let body = std::str::from_utf8(&request.body).unwrap();
+            match (request.method, path) {
+                (Method::Post, "/logstores") if body.contains("autoSplit") => {
+                    assert_eq!(
+                        body,
+                        r#"{"appendMeta":false,"autoSplit":true,"logstoreName":"access-log","maxSplitShard":64,"shardCount":4,"ttl":30}"#
+                    );
+                    HttpResponse::new(400).with_body(
+                        r#"{"errorCode":"LogStoreAlreadyExist","errorMessage":"exists"}"#,
+                    )
+                }
+                (Method::Post, "/logstores") => {
+                    assert_eq!(
+                        body,
+                        r#"{"appendMeta":false,"logstoreName":"audit-log","shardCount":2,"ttl":30}"#
+                    );
+                    HttpResponse::new(200)
+                }
+                (Method::Get, "/logstores?offset=0&size=500") => HttpResponse::new(200)
+                    .with_body(r#"{"count":1,"total":2,"logstores":["access-log"]}"#),
+                (Method::Get, "/logstores?offset=1&size=500") => HttpResponse::new(200)
+                    .with_body(r#"{"count":1,"total":2,"logstores":["audit-log"]}"#),
+                (Method::Get, "/logstores/access-log/shards") => HttpResponse::new(200).with_body(
+                    r#"[{"shardID":0,"status":"readwrite","inclusiveBeginKey":"00000000000000000000000000000000","exclusiveEndKey":"80000000000000000000000000000000","createTime":1700000000}]"#,
+                ),
+                (
+                    Method::Post,
+                    "/logstores/access-log/shards/0?action=split&key=40000000000000000000000000000000",
+                ) => HttpResponse::new(200).with_body("[]"),
+                (Method::Post, "/logstores/access-log/shards/0?action=merge") => {
+                    HttpResponse::new(200).with_body(
+                        r#"[{"shardID":2,"status":"readwrite","inclusiveBeginKey":"00000000000000000000000000000000","exclusiveEndKey":"ffffffffffffffffffffffffffffffff","createTime":1700000000}]"#,
+                    )
+                }
+                _ => HttpResponse::new(404),
+            }
+        });
+        let client = test_client().transport(transport.clone()).build().unwrap();
+
+        let logstore = LogStore::new("access-log", 30)
+            .with_shard_count(4)
+            .with_auto_split(64);
+        let err = client
+            .create_logstore("playground", &logstore)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.code().map(|code| code.as_str()),
+            Some("LogStoreAlreadyExist")
+        );
+        let err = client
+            .create_logstore("playground", &LogStore::new("Invalid", 30))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlsClientError::InvalidTarget(_)));
+        client
+            .create_logstore("playground", &LogStore::new("audit-log", 30))
+            .await
+            .unwrap();
+
+        let logstores = client.list_logstores("playground").await.unwrap();
+        assert_eq!(logstores, ["access-log", "audit-log"]);
+
+        let target = Target::new("playground", "access-log");
+        client
+            .split_shard(&target, 0, HashKey::new(1 << 126))
+            .await
+            .unwrap();
+        let requests = transport.requests().len();
+        let err = client
+            .split_shard(&target, 0, HashKey::new(1 << 127))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlsClientError::InvalidArgument(_)));
+        // Only the shards are listed
+        assert_eq!(transport.requests().len(), requests + 1);
+
+        let shards = client.merge_shards(&target, 0).await.unwrap();
+        assert_eq!(shards[0].id, 2);
+        assert_eq!(shards[0].end_key, HashKey::new(u128::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_retry_non_idempotent() {
+        let transport = FakeTransport::new(|_| HttpResponse::new(503));
+        let client = |retry_policy: RetryPolicy| {
+            test_client()
+                .transport(transport.clone())
+                .retry_policy(retry_policy.with_base_delay(Default::default()))
+                .build()
+                .unwrap()
+        };
+        let logstore = LogStore::new("access-log", 30);
+
+        let client_default = client(RetryPolicy::new(|_| async {}));
+        client_default
+            .create_logstore("playground", &logstore)
+            .await
+            .unwrap_err();
+        assert_eq!(transport.requests().len(), 1);
+        client_default
+            .get_logstore("playground", "access-log")
+            .await
+            .unwrap_err();
+        assert_eq!(transport.requests().len(), 4);
+
+        client(RetryPolicy::new(|_| async {}).with_retry_non_idempotent(true))
+            .create_logstore("playground", &logstore)
+            .await
+            .unwrap_err();
+        assert_eq!(transport.requests().len(), 7);
+    }
+}
@@ -1,17 +1,55 @@
 //! Aliyun SLS client
 
-pub use self::builder::{SlsClientBuilder, SlsClientBuilderError};
+#[cfg(feature = "blocking")]
+pub use self::blocking::BlockingSlsClient;
+pub use self::{
+    builder::{SlsClientBuilder, SlsClientBuilderError},
+    compression::Compression,
+    consumer_group::{Checkpoint, ConsumerGroup},
+    endpoint::Endpoint,
+    error::{ErrorCode, SlsClientError},
+    hash_key::{HashKey, ParseHashKeyError},
+    index::{FieldIndex, FieldType, FullTextIndex, Index},
+    logstore::LogStore,
+    pull::{Cursor, CursorPosition, PullLogsResponse, Shard, ShardStatus},
+    query::{GetLogs, GetLogsResponse},
+    retry::{Jitter, RetryPolicy, RetryTimer},
+    signer::SignatureVersion,
+    target::Target,
+};
 use crate::{
     Log, LogGroupMetadata,
-    proto::{calc_log_group_encoded_len, encode_log_group},
+    credentials::CredentialsCache,
+    proto::{calc_log_group_encoded_len, encode_log_group, split_log_group},
+    transport::{HttpRequest, HttpResponse, HttpTransport, Method},
 };
-use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use std::{collections::BTreeMap, sync::Arc};
 use tracing::{Instrument, Level};
 
+#[cfg(feature = "blocking")]
+mod blocking;
 mod builder;
+mod compression;
+mod consumer_group;
+mod endpoint;
+mod error;
+mod hash_key;
 mod headers;
-mod imp;
-mod signer;
+pub(crate) mod imp;
+mod index;
+mod logstore;
+mod naming;
+mod pull;
+mod query;
+mod retry;
+pub(crate) mod signer;
+mod target;
+
+/// Maximum number of logs in a PutLogs request.
+const MAX_LOGS_PER_PUT: usize = 4096;
+/// Maximum raw size of the log group of a PutLogs request.
+const MAX_PUT_SIZE: usize = 5 * 1024 * 1024;
 
 /// A client for sending logs to Aliyun SLS (Simple Log Service).
 #[derive(Clone)]
@@ -20,29 +58,15 @@ pub struct SlsClient {
 }
 
 struct SlsClientInner {
-    url: String,
+    origin: endpoint::Origin,
+    target: Option<Target>,
+    credentials: CredentialsCache,
     signer: signer::Signer,
+    transport: Arc<dyn HttpTransport>,
     enable_trace: bool,
     print_internal_error: bool,
-    #[cfg(feature = "deflate")]
-    compression_level: u8,
-}
-
-/// Error type for SLS client operations.
-#[derive(Debug, thiserror::Error)]
-#[non_exhaustive]
-pub enum SlsClientError {
-    /// Non-successful HTTP response from the SLS service.
-    #[error("http error [{status}] {message}")]
-    Http {
-        /// HTTP status code.
-        status: u16,
-        /// Error message from the response.
-        message: Box<str>,
-    },
-    /// Other HTTP client error.
-    #[error("other http client error: {0}")]
-    Imp(#[from] imp::Error),
+    compression: Compression,
+    retry_policy: Option<RetryPolicy>,
 }
 
 impl SlsClient {
@@ -51,32 +75,152 @@ impl SlsClient {
         SlsClientBuilder::default()
     }
 
-    /// Put a log group to Aliyun SLS.
+    /// Whether the client logs via `tracing`.
+    #[cfg(feature = "consumer")]
+    pub(crate) fn enable_trace(&self) -> bool {
+        self.inner.enable_trace
+    }
+
+    /// Put a log group to the default target of the client.
     pub async fn put_log(&self, metadata: &LogGroupMetadata, logs: &[Log]) {
         self.try_put_log(metadata, logs).await.ok();
     }
 
-    /// Try to put a log group to Aliyun SLS.
+    /// Try to put a log group to the default target of the client.
+    ///
+    /// Log groups over the PutLogs limits of 4096 logs or 5 MB are split into chunks sharing
+    /// the metadata, each sent separately. If only some chunks fail to be sent,
+    /// fails with [`SlsClientError::PartialFailure`].
+    ///
+    /// Fails with [`SlsClientError::InvalidTarget`] if the client has no default target.
     pub async fn try_put_log(
         &self,
         metadata: &LogGroupMetadata,
         logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        self.try_put_log_inner(self.inner.target.as_ref(), None, metadata, logs)
+            .await
+    }
+
+    /// Put a log group to the default target of the client, routed by the given hash key.
+    pub async fn put_log_with_key(
+        &self,
+        hash_key: HashKey,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) {
+        self.try_put_log_with_key(hash_key, metadata, logs)
+            .await
+            .ok();
+    }
+
+    /// Try to put a log group to the default target of the client, routed by the given hash key.
+    ///
+    /// The hash key overrides the one of the default target, if any.
+    pub async fn try_put_log_with_key(
+        &self,
+        hash_key: HashKey,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        self.try_put_log_inner(self.inner.target.as_ref(), Some(hash_key), metadata, logs)
+            .await
+    }
+
+    /// Put a log group to the given target.
+    pub async fn put_log_to(&self, target: &Target, metadata: &LogGroupMetadata, logs: &[Log]) {
+        self.try_put_log_to(target, metadata, logs).await.ok();
+    }
+
+    /// Try to put a log group to the given target.
+    pub async fn try_put_log_to(
+        &self,
+        target: &Target,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
+        self.try_put_log_inner(Some(target), None, metadata, logs)
+            .await
+    }
+
+    /// Send a signed request to the project of the default target, for APIs without wrapper.
+    ///
+    /// Fails with [`SlsClientError::InvalidTarget`] if the client has no default target.
+    pub async fn request(
+        &self,
+        method: Method,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        headers: &BTreeMap<String, String>,
+        body: &[u8],
+    ) -> Result<HttpResponse, SlsClientError> {
+        let target = self.inner.target.as_ref().ok_or_else(|| {
+            SlsClientError::InvalidTarget("no default target, use `request_to`".into())
+        })?;
+        self.request_to(target.project(), method, resource, query, headers, body)
+            .await
+    }
+
+    /// Send a signed request to `project`, for APIs without wrapper.
+    ///
+    /// `resource` is the path of the API, e.g. `/logstores/test/index`, and `x-log-*` and
+    /// `x-acs-*` headers are signed. The request is retried like any other, and the raw response
+    /// is returned on success, or parsed as an [`SlsClientError::Http`] otherwise.
+    pub async fn request_to(
+        &self,
+        project: &str,
+        method: Method,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        headers: &BTreeMap<String, String>,
+        body: &[u8],
+    ) -> Result<HttpResponse, SlsClientError> {
+        validate_project(project)?;
+        // Signed headers are canonicalized with lowercase names
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        self.retry("request", method.is_idempotent(), || {
+            self.send(method, project, resource, query, headers.clone(), body)
+        })
+        .await
+    }
+
+    async fn try_put_log_inner(
+        &self,
+        target: Option<&Target>,
+        hash_key: Option<HashKey>,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
     ) -> Result<(), SlsClientError> {
         let fut = async move {
-            return match self.put_log_inner(metadata, logs).await {
-                Err(e) => {
-                    if self.inner.enable_trace {
-                        tracing::error!(err = ?e);
-                    } else if self.inner.print_internal_error {
-                        eprintln!("[tracing-aliyun-sls] error putting log: {e}");
-                    }
-                    Err(e)
+            let result = match target {
+                Some(target) => {
+                    let hash_key = hash_key.or(target.hash_key());
+                    self.put_log_inner(target, hash_key, metadata, logs).await
                 }
-                Ok(()) => Ok(()),
+                None => Err(SlsClientError::InvalidTarget(
+                    "no default target, use `put_log_to`".into(),
+                )),
             };
+            if let Err(e) = &result {
+                if self.inner.enable_trace {
+                    tracing::error!(err = ?e);
+                } else if self.inner.print_internal_error {
+                    eprintln!("[tracing-aliyun-sls] error putting log: {e}");
+                }
+            }
+            result
         };
         if self.inner.enable_trace {
-            fut.instrument(tracing::span!(Level::TRACE, "put_log", target = %self.inner.signer.canonicalized_resource)).await
+            let span = tracing::span!(
+                Level::TRACE,
+                "put_log",
+                project = target.map(Target::project),
+                logstore = target.map(Target::logstore),
+            );
+            fut.instrument(span).await
         } else {
             fut.await
         }
@@ -84,47 +228,270 @@ impl SlsClient {
 
     async fn put_log_inner(
         &self,
+        target: &Target,
+        hash_key: Option<HashKey>,
         metadata: &LogGroupMetadata,
         logs: &[Log],
     ) -> Result<(), SlsClientError> {
-        let http_client = imp::HttpClient::get_or_try_init().await?;
+        validate_target(target)?;
+        let mut query = BTreeMap::new();
+        let resource = match hash_key {
+            None => format!("/logstores/{}/shards/lb", target.logstore()),
+            Some(hash_key) => {
+                query.insert("key".to_string(), hash_key.to_string());
+                format!("/logstores/{}/shards/route", target.logstore())
+            }
+        };
+
+        let chunks = split_log_group(metadata, logs, MAX_LOGS_PER_PUT, MAX_PUT_SIZE);
+        if let [logs] = chunks[..] {
+            return self
+                .put_chunk(target.project(), &resource, &query, metadata, logs)
+                .await;
+        }
+        if self.inner.enable_trace {
+            tracing::debug!(chunks = chunks.len(), "splitting oversized log group");
+        }
+
+        let (mut start, mut failed, mut source) = (0, Vec::new(), None);
+        for logs in &chunks {
+            let range = start..start + logs.len();
+            start = range.end;
+            if let Err(e) = self
+                .put_chunk(target.project(), &resource, &query, metadata, logs)
+                .await
+            {
+                failed.push(range);
+                source.get_or_insert(e);
+            }
+        }
+        match source {
+            None => Ok(()),
+            Some(e) if failed.len() == chunks.len() => Err(e),
+            Some(e) => Err(SlsClientError::PartialFailure {
+                failed,
+                source: Box::new(e),
+            }),
+        }
+    }
 
+    /// Put logs fitting in a single PutLogs request, with retry.
+    async fn put_chunk(
+        &self,
+        project: &str,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        metadata: &LogGroupMetadata,
+        logs: &[Log],
+    ) -> Result<(), SlsClientError> {
         let raw_length = calc_log_group_encoded_len(metadata, logs);
         let mut buf = Vec::with_capacity(raw_length);
         encode_log_group(&mut buf, metadata, logs).expect("infallible");
-        #[cfg(feature = "lz4")]
-        let buf = lz4_flex::compress(&buf);
-        #[cfg(feature = "deflate")]
-        let buf = miniz_oxide::deflate::compress_to_vec_zlib(&buf, self.inner.compression_level);
-
-        let signature = self.inner.signer.sign(raw_length, &buf);
-        let builder = http_client
-            .post(&self.inner.url)
-            .header(headers::AUTHORIZATION, signature.authorization)
-            .header(headers::CONTENT_LENGTH, buf.len().to_string())
-            .header(headers::CONTENT_MD5, signature.content_md5)
-            .header(headers::DATE, signature.date)
-            .header(headers::LOG_BODY_RAW_SIZE, signature.raw_length);
-
-        #[cfg(feature = "lz4")]
-        let builder = builder.header(headers::LOG_COMPRESS_TYPE, "lz4");
-        #[cfg(feature = "deflate")]
-        let builder = builder.header(headers::LOG_COMPRESS_TYPE, "deflate");
+        let buf = self.inner.compression.compress(buf);
 
-        let res = builder.body(buf).send().await?;
-        if self.inner.enable_trace {
-            let status = res.status();
-            let res = res.text().await?;
-            tracing::trace!(%status, %res);
-            if !status.is_success() {
-                return Err(SlsClientError::Http {
-                    status: status.into(),
-                    message: res.into_boxed_str(),
-                });
+        let mut headers = BTreeMap::from([
+            (
+                headers::CONTENT_TYPE.to_string(),
+                headers::DEFAULT_CONTENT_TYPE.to_string(),
+            ),
+            (
+                headers::LOG_BODY_RAW_SIZE.to_string(),
+                raw_length.to_string(),
+            ),
+        ]);
+        if let Some(compress_type) = self.inner.compression.header_value() {
+            headers.insert(
+                headers::LOG_COMPRESS_TYPE.to_string(),
+                compress_type.to_string(),
+            );
+        }
+        self.retry("put_log", true, || async {
+            let mut request = self
+                .sign(
+                    Method::Post,
+                    project,
+                    resource,
+                    query,
+                    headers.clone(),
+                    &buf,
+                )
+                .await?;
+            // Only read the body if someone needs it
+            request.read_body = self.inner.enable_trace;
+            self.execute(request).await
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Send a request with an optional JSON body to `project`, with retry.
+    ///
+    /// The request is only retried by default if `idempotent`, see [`RetryPolicy`].
+    #[allow(clippy::too_many_arguments)]
+    async fn send_json(
+        &self,
+        operation: &str,
+        method: Method,
+        idempotent: bool,
+        project: &str,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        body: Option<serde_json::Value>,
+    ) -> Result<HttpResponse, SlsClientError> {
+        let mut headers = BTreeMap::new();
+        let body = match body {
+            Some(body) => {
+                headers.insert(
+                    headers::CONTENT_TYPE.to_string(),
+                    headers::JSON_CONTENT_TYPE.to_string(),
+                );
+                body.to_string().into_bytes()
+            }
+            None => Vec::new(),
+        };
+        self.retry(operation, idempotent, || {
+            self.send(method, project, resource, query, headers.clone(), &body)
+        })
+        .await
+    }
+
+    /// Run the request until it succeeds, or the retry policy gives up.
+    ///
+    /// Non-idempotent requests are only retried if the retry policy allows it.
+    async fn retry<T, F, Fut>(
+        &self,
+        operation: &str,
+        idempotent: bool,
+        mut request: F,
+    ) -> Result<T, SlsClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SlsClientError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let e = match request().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            let Some(retry_policy) = &self.inner.retry_policy else {
+                return Err(e);
+            };
+            let Some(delay) = retry_policy.next_delay(attempt, idempotent, &e) else {
+                return Err(e);
+            };
+            if self.inner.enable_trace {
+                tracing::warn!(attempt, ?delay, err = ?e, "retrying {operation}");
             }
+            retry_policy.sleep(delay).await;
+            attempt += 1;
         }
-        Ok(())
     }
+
+    /// Sign and send a request to `project`, failing on non-successful responses.
+    ///
+    /// `x-log-bodyrawsize` defaults to the length of the body.
+    async fn send(
+        &self,
+        method: Method,
+        project: &str,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        headers: BTreeMap<String, String>,
+        body: &[u8],
+    ) -> Result<HttpResponse, SlsClientError> {
+        let request = self
+            .sign(method, project, resource, query, headers, body)
+            .await?;
+        self.execute(request).await
+    }
+
+    /// Sign a request to `project`, returning it ready to be sent.
+    async fn sign(
+        &self,
+        method: Method,
+        project: &str,
+        resource: &str,
+        query: &BTreeMap<String, String>,
+        mut headers: BTreeMap<String, String>,
+        body: &[u8],
+    ) -> Result<HttpRequest, SlsClientError> {
+        let credentials = self.inner.credentials.get().await?;
+
+        headers.insert(headers::HOST.to_string(), self.inner.origin.host(project));
+        headers
+            .entry(headers::LOG_BODY_RAW_SIZE.to_string())
+            .or_insert_with(|| body.len().to_string());
+        self.inner.signer.sign(
+            &credentials,
+            signer::SignRequest {
+                method,
+                resource,
+                query,
+                headers: &mut headers,
+                body,
+            },
+        );
+
+        let mut url = self
+            .inner
+            .origin
+            .url(project, &signer::encode_path(resource));
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&signer::canonicalized_query(query));
+        }
+        Ok(HttpRequest {
+            method,
+            url,
+            // Unless path-style, the host is derived from the url by the transport
+            headers: headers
+                .into_iter()
+                .filter(|(name, _)| self.inner.origin.is_path_style() || name != headers::HOST)
+                .collect(),
+            body: body.to_vec(),
+            timeout: None,
+            read_body: true,
+        })
+    }
+
+    /// Send a signed request, failing on non-successful responses.
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, SlsClientError> {
+        let res = self.inner.transport.send(request).await?;
+        if self.inner.enable_trace {
+            tracing::trace!(status = res.status, res = %res.text());
+        }
+        if !res.is_success() {
+            return Err(SlsClientError::from_response(
+                res.status,
+                res.header(headers::LOG_REQUEST_ID).map(str::to_string),
+                res.text(),
+            ));
+        }
+        Ok(res)
+    }
+}
+
+/// Check the project name, failing with [`SlsClientError::InvalidTarget`].
+fn validate_project(project: &str) -> Result<(), SlsClientError> {
+    naming::check_project(project).map_err(|reason| {
+        SlsClientError::InvalidTarget(format!("project name {project:?} {reason}").into())
+    })
+}
+
+/// Check the project and logstore names of `target`,
+/// failing with [`SlsClientError::InvalidTarget`].
+fn validate_target(target: &Target) -> Result<(), SlsClientError> {
+    validate_project(target.project())?;
+    let logstore = target.logstore();
+    naming::check_logstore(logstore).map_err(|reason| {
+        SlsClientError::InvalidTarget(format!("logstore name {logstore:?} {reason}").into())
+    })
+}
+
+/// Deserialize the JSON body of a response.
+fn parse_json<T: DeserializeOwned>(res: &HttpResponse) -> Result<T, SlsClientError> {
+    serde_json::from_slice(&res.body).map_err(|e| SlsClientError::InvalidResponse(e.into()))
 }
 
 #[cfg(test)]
@@ -145,6 +512,7 @@ mod test {
             .enable_trace(true);
 
         #[cfg(feature = "deflate")]
+        #[allow(deprecated)]
         let builder = builder.compression_level(10);
 
         let client = builder.build().unwrap();
@@ -155,4 +523,272 @@ mod test {
 
         client.put_log(&metadata, &logs).await;
     }
+
+    #[tokio::test]
+    async fn test_transport() {
+        use crate::{
+            client::{ErrorCode, SlsClientError},
+            proto::*,
+            test_utils::{FakeTransport, test_client},
+            transport::HttpResponse,
+        };
+
+        let transport = FakeTransport::new(|_| {
+            HttpResponse::new(403)
+                .with_header("x-log-requestid", "request-id")
+                .with_body(
+                    r#"{"errorCode":"SignatureNotMatch","errorMessage":"signature not match"}"#,
+                )
+        });
+        let client = test_client()
+            .project("playground")
+            .logstore("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let logs = vec![Log::default().with(MayStaticKey::from_static("message"), "hello world")];
+        let err = client
+            .try_put_log(&LogGroupMetadata::default(), &logs)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlsClientError::Http { status: 403, .. }));
+        assert_eq!(err.code(), Some(&ErrorCode::SignatureNotMatch));
+        assert_eq!(err.request_id(), Some("request-id"));
+
+        let [request] = &transport.requests()[..] else {
+            panic!("expected a single request");
+        };
+        assert_eq!(
+            request.url,
+            "https://playground.cn-guangzhou.log.aliyuncs.com/logstores/test/shards/lb"
+        );
+        assert!(
+            request
+                .headers
+                .iter()
+                .any(|(name, _)| name == "authorization")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disable_trace() {
+        use crate::{
+            client::SlsClientError,
+            proto::*,
+            test_utils::{FakeTransport, test_client},
+            transport::HttpResponse,
+        };
+        use std::sync::{
+            Arc,
+            atomic::{AtomicU16, Ordering},
+        };
+
+        let status = Arc::new(AtomicU16::new(200));
+        let transport = FakeTransport::new({
+            let status = status.clone();
+            move |_| HttpResponse::new(status.load(Ordering::Relaxed))
+        });
+        let client = test_client()
+            .project("playground")
+            .logstore("test")
+            .enable_trace(false)
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let metadata = LogGroupMetadata::default();
+        let logs = vec![Log::default().with(MayStaticKey::from_static("message"), "hello world")];
+        client.try_put_log(&metadata, &logs).await.unwrap();
+        let [request] = &transport.requests()[..] else {
+            panic!("expected a single request");
+        };
+        assert!(!request.read_body);
+
+        for code in [401, 500] {
+            status.store(code, Ordering::Relaxed);
+            let err = client.try_put_log(&metadata, &logs).await.unwrap_err();
+            assert!(
+                matches!(err, SlsClientError::Http { status, .. } if status == code),
+                "unexpected error: {err}"
+            );
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_invalid_http_config() {
+        use crate::{
+            client::SlsClientBuilderError,
+            test_utils::test_client,
+            transport::{HttpBackend, HttpConfig},
+        };
+
+        let err = test_client()
+            .project("playground")
+            .logstore("test")
+            .http_backend(HttpBackend::Reqwest)
+            .http_config(HttpConfig::default().with_proxy("http://[::1"))
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, SlsClientBuilderError::InvalidHttpConfig(_)));
+    }
+
+    #[tokio::test]
+    async fn test_base_url() {
+        use crate::{
+            proto::*,
+            test_utils::{serve, test_client},
+        };
+
+        let base_url = serve(|target| match target {
+            "/logstores/test/shards/lb" => (200, String::new()),
+            _ => (404, String::new()),
+        })
+        .await;
+        let client = test_client()
+            .base_url(&base_url)
+            .path_style(true)
+            .project("playground")
+            .logstore("test")
+            .build()
+            .unwrap();
+
+        let logs = vec![Log::default().with(MayStaticKey::from_static("message"), "hello world")];
+        client
+            .try_put_log(&LogGroupMetadata::default(), &logs)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_log_to() {
+        use crate::{
+            client::{HashKey, SlsClientError, Target},
+            proto::*,
+            test_utils::{FakeTransport, test_client},
+            transport::HttpResponse,
+        };
+
+        let transport = FakeTransport::new(|_| HttpResponse::new(200));
+        let client = test_client().transport(transport.clone()).build().unwrap();
+
+        let metadata = LogGroupMetadata::default();
+        let logs = vec![Log::default().with(MayStaticKey::from_static("message"), "hello world")];
+        let err = client.try_put_log(&metadata, &logs).await.unwrap_err();
+        assert!(matches!(err, SlsClientError::InvalidTarget(_)));
+
+        let audit = Target::new("playground", "audit");
+        let access = Target::new("other", "access").with_hash_key(HashKey::new(1));
+        client
+            .try_put_log_to(&audit, &metadata, &logs)
+            .await
+            .unwrap();
+        client
+            .try_put_log_to(&access, &metadata, &logs)
+            .await
+            .unwrap();
+        let err = client
+            .try_put_log_to(&Target::new("Invalid", "access"), &metadata, &logs)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlsClientError::InvalidTarget(_)));
+
+        let urls = transport
+            .requests()
+            .into_iter()
+            .map(|request| request.url)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            [
+                "https://playground.cn-guangzhou.log.aliyuncs.com/logstores/audit/shards/lb",
+                "https://other.cn-guangzhou.log.aliyuncs.com/logstores/access/shards/route?key=00000000000000000000000000000001",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split() {
+        use crate::{
+            client::SlsClientError,
+            proto::*,
+            test_utils::{FakeTransport, test_client},
+            transport::HttpResponse,
+        };
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let n = AtomicU32::new(0);
+        let transport = FakeTransport::new(move |_| {
+            HttpResponse::new(if n.fetch_add(1, Ordering::Relaxed) == 0 {
+                200
+            } else {
+                500
+            })
+        });
+        let client = test_client()
+            .project("playground")
+            .logstore("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let logs =
+            vec![Log::default().with(MayStaticKey::from_static("message"), "hello world"); 9000];
+        let err = client
+            .try_put_log(&LogGroupMetadata::default(), &logs)
+            .await
+            .unwrap_err();
+        let SlsClientError::PartialFailure { failed, source } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(failed, [4096..8192, 8192..9000]);
+        assert_eq!(source.status(), Some(500));
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_request() {
+        use crate::{
+            test_utils::{FakeTransport, test_client},
+            transport::{HttpResponse, Method},
+        };
+        use std::collections::BTreeMap;
+
+        let transport =
+            FakeTransport::new(|_| HttpResponse::new(200).with_body(r#"{"line":null}"#));
+        let client = test_client()
+            .project("playground")
+            .logstore("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let query = BTreeMap::from([("lang".to_string(), "en".to_string())]);
+        let headers = BTreeMap::from([("X-Log-Custom".to_string(), "value".to_string())]);
+        let res = client
+            .request(Method::Get, "/logstores/test/index", &query, &headers, &[])
+            .await
+            .unwrap();
+        assert_eq!(res.text(), r#"{"line":null}"#);
+
+        let [request] = &transport.requests()[..] else {
+            panic!("expected a single request");
+        };
+        assert_eq!(request.method, Method::Get);
+        assert_eq!(
+            request.url,
+            "https://playground.cn-guangzhou.log.aliyuncs.com/logstores/test/index?lang=en"
+        );
+        let header = |name| {
+            request
+                .headers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(header("x-log-custom"), Some("value"));
+        assert!(header("authorization").is_some());
+    }
 }
@@ -0,0 +1,63 @@
+//! Naming rules of SLS resources, checked before sending any request.
+
+/// Check a project name, returning the reason it is invalid.
+///
+/// A project name has 3 to 63 lowercase letters, digits and hyphens,
+/// and starts and ends with a lowercase letter or digit.
+pub(crate) fn check_project(name: &str) -> Result<(), &'static str> {
+    if !(3..=63).contains(&name.len()) {
+        return Err("must be 3 to 63 characters long");
+    }
+    if !name.bytes().all(|b| is_alphanumeric(b) || b == b'-') {
+        return Err("must only contain lowercase letters, digits and hyphens");
+    }
+    check_ends(name)
+}
+
+/// Check a logstore name, returning the reason it is invalid.
+///
+/// A logstore name has 2 to 63 lowercase letters, digits, hyphens and underscores,
+/// and starts and ends with a lowercase letter or digit.
+pub(crate) fn check_logstore(name: &str) -> Result<(), &'static str> {
+    if !(2..=63).contains(&name.len()) {
+        return Err("must be 2 to 63 characters long");
+    }
+    if !name
+        .bytes()
+        .all(|b| is_alphanumeric(b) || b == b'-' || b == b'_')
+    {
+        return Err("must only contain lowercase letters, digits, hyphens and underscores");
+    }
+    check_ends(name)
+}
+
+fn is_alphanumeric(b: u8) -> bool {
+    b.is_ascii_lowercase() || b.is_ascii_digit()
+}
+
+fn check_ends(name: &str) -> Result<(), &'static str> {
+    let bytes = name.as_bytes();
+    if !is_alphanumeric(bytes[0]) || !is_alphanumeric(bytes[bytes.len() - 1]) {
+        return Err("must start and end with a lowercase letter or digit");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naming() {
+        assert!(check_project("my-project-1").is_ok());
+        assert!(check_project("ab").is_err());
+        assert!(check_project("My-Project").is_err());
+        assert!(check_project("my_project").is_err());
+        assert!(check_project("-project").is_err());
+
+        assert!(check_logstore("access_log").is_ok());
+        assert!(check_logstore("a").is_err());
+        assert!(check_logstore("access_log_").is_err());
+        assert!(check_logstore(&"a".repeat(64)).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    LogGroup,
+    client::{Compression, HashKey, SlsClient, SlsClientError, Target, headers, validate_target},
+    transport::{HttpResponse, Method},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{collections::BTreeMap, fmt};
+
+/// Shard of a logstore, returned by [`SlsClient::list_shards`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Shard {
+    /// Shard id.
+    #[serde(rename = "shardID")]
+    pub id: u32,
+    /// Whether the shard can be written to.
+    pub status: ShardStatus,
+    /// Start of the hash range of the shard, inclusive.
+    #[serde(rename = "inclusiveBeginKey")]
+    pub begin_key: HashKey,
+    /// End of the hash range of the shard, exclusive.
+    #[serde(rename = "exclusiveEndKey")]
+    pub end_key: HashKey,
+    /// Creation time of the shard, as a UNIX timestamp in seconds.
+    pub create_time: u32,
+}
+
+/// Status of a [`Shard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ShardStatus {
+    /// The shard can be read and written.
+    ReadWrite,
+    /// The shard was split or merged, and can only be read.
+    ReadOnly,
+    /// Unknown status.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Position of a shard to read from, see [`SlsClient::get_cursor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorPosition {
+    /// The oldest log still stored in the shard.
+    Begin,
+    /// After the latest log of the shard.
+    End,
+    /// The first log received at or after the UNIX timestamp in seconds.
+    Time(u32),
+}
+
+/// Opaque position in a shard, returned by the SLS service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cursor(String);
+
+/// Result of a PullLogs request.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PullLogsResponse {
+    /// Log groups read from the shard.
+    pub log_groups: Vec<LogGroup>,
+    /// Cursor to continue reading from.
+    ///
+    /// Equal to the requested cursor if there is no new log yet.
+    pub next_cursor: Cursor,
+}
+
+impl Cursor {
+    /// Create a cursor from its string value, e.g. one saved before.
+    pub fn new(cursor: impl Into<String>) -> Self {
+        Self(cursor.into())
+    }
+
+    /// String value of the cursor.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl SlsClient {
+    /// List the shards of the logstore of the given target.
+    pub async fn list_shards(&self, target: &Target) -> Result<Vec<Shard>, SlsClientError> {
+        let resource = format!("/logstores/{}/shards", target.logstore());
+        self.get_json(target, "list_shards", &resource, BTreeMap::new())
+            .await
+    }
+
+    /// Get the cursor of a shard at the given position.
+    pub async fn get_cursor(
+        &self,
+        target: &Target,
+        shard: u32,
+        position: CursorPosition,
+    ) -> Result<Cursor, SlsClientError> {
+        #[derive(Deserialize)]
+        struct Body {
+            cursor: String,
+        }
+
+        let from = match position {
+            CursorPosition::Begin => "begin".to_string(),
+            CursorPosition::End => "end".to_string(),
+            CursorPosition::Time(time) => time.to_string(),
+        };
+        let resource = format!("/logstores/{}/shards/{shard}", target.logstore());
+        let query = BTreeMap::from([
+            ("type".to_string(), "cursor".to_string()),
+            ("from".to_string(), from),
+        ]);
+        let body: Body = self
+            .get_json(target, "get_cursor", &resource, query)
+            .await?;
+        Ok(Cursor(body.cursor))
+    }
+
+    /// Read at most `count` log groups of a shard, from `cursor`.
+    ///
+    /// Responses compressed with `lz4`, `deflate` or `zstd` are decompressed transparently,
+    /// the first enabled of these codecs is requested.
+    /// Fails with [`SlsClientError::Unsupported`] if none of them is enabled,
+    /// as the service always compresses the response.
+    pub async fn pull_logs(
+        &self,
+        target: &Target,
+        shard: u32,
+        cursor: &Cursor,
+        count: u32,
+    ) -> Result<PullLogsResponse, SlsClientError> {
+        validate_target(target)?;
+        let encoding = Compression::accept_encoding().ok_or(SlsClientError::Unsupported(
+            "pulling logs requires one of the `lz4`, `deflate` and `zstd` features",
+        ))?;
+        let resource = format!("/logstores/{}/shards/{shard}", target.logstore());
+        let query = BTreeMap::from([
+            ("type".to_string(), "log".to_string()),
+            ("cursor".to_string(), cursor.0.clone()),
+            ("count".to_string(), count.to_string()),
+        ]);
+        let headers = BTreeMap::from([
+            (
+                headers::ACCEPT.to_string(),
+                headers::DEFAULT_CONTENT_TYPE.to_string(),
+            ),
+            (headers::ACCEPT_ENCODING.to_string(), encoding.to_string()),
+        ]);
+        let res = self
+            .retry("pull_logs", true, || {
+                self.send(
+                    Method::Get,
+                    target.project(),
+                    &resource,
+                    &query,
+                    headers.clone(),
+                    &[],
+                )
+            })
+            .await?;
+
+        let next_cursor = res
+            .header(headers::LOG_CURSOR)
+            .ok_or_else(|| invalid_response("missing x-log-cursor header"))?;
+        let body = decompress(&res)?;
+        Ok(PullLogsResponse {
+            log_groups: LogGroup::decode_list(&body)
+                .map_err(|e| SlsClientError::InvalidResponse(e.into()))?,
+            next_cursor: Cursor::new(next_cursor),
+        })
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        target: &Target,
+        operation: &str,
+        resource: &str,
+        query: BTreeMap<String, String>,
+    ) -> Result<T, SlsClientError> {
+        validate_target(target)?;
+        let res = self
+            .retry(operation, true, || {
+                self.send(
+                    Method::Get,
+                    target.project(),
+                    resource,
+                    &query,
+                    BTreeMap::new(),
+                    &[],
+                )
+            })
+            .await?;
+        serde_json::from_slice(&res.body).map_err(|e| SlsClientError::InvalidResponse(e.into()))
+    }
+}
+
+fn invalid_response(message: &str) -> SlsClientError {
+    SlsClientError::InvalidResponse(message.into())
+}
+
+/// Decompress the body according to the `x-log-compresstype` and `x-log-bodyrawsize` headers.
+fn decompress(res: &HttpResponse) -> Result<Vec<u8>, SlsClientError> {
+    let compress_type = res.header(headers::LOG_COMPRESS_TYPE).unwrap_or_default();
+    let compression = Compression::from_header_value(compress_type)
+        .ok_or_else(|| invalid_response(&format!("unsupported compress type {compress_type:?}")))?;
+    let raw_size = match res.header(headers::LOG_BODY_RAW_SIZE) {
+        Some(raw_size) => raw_size
+            .parse()
+            .map_err(|_| invalid_response("invalid x-log-bodyrawsize header"))?,
+        None => res.body.len(),
+    };
+    compression
+        .decompress(&res.body, raw_size)
+        .map_err(|e| SlsClientError::InvalidResponse(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Cursor, Target,
+        test_utils::{FakeTransport, test_client},
+        transport::HttpResponse,
+    };
+
+    #[cfg(any(feature = "lz4", feature = "deflate", feature = "zstd"))]
+    #[tokio::test]
+    async fn test_pull_logs() {
+        use crate::{Compression, CursorPosition, HashKey, LogGroup, ShardStatus, proto::*};
+
+        let metadata = LogGroupMetadata::new().with_topic("topic");
+        let logs =
+            vec![Log::new(1700000000, None).with(MayStaticKey::from_static("message"), "hello")];
+        let mut group = Vec::new();
+        encode_log_group(&mut group, &metadata, &logs).unwrap();
+        assert!(group.len() < 0x80);
+        // LogGroupList with the group as its only field
+        let list = [&[0x0a, group.len() as u8], group.as_slice()].concat();
+
+        let transport = FakeTransport::new(move |request| {
+            let path = request
+                .url
+                .strip_prefix("https://playground.cn-guangzhou.log.aliyuncs.com")
+                .unwrap();
+            match path {
+                "/logstores/test/shards" => HttpResponse::new(200).with_body(
+                    r#"[{"shardID":0,"status":"readwrite","inclusiveBeginKey":"00000000000000000000000000000000","exclusiveEndKey":"80000000000000000000000000000000","createTime":1700000000}]"#,
+                ),
+                "/logstores/test/shards/0?from=begin&type=cursor" => {
+                    HttpResponse::new(200).with_body(r#"{"cursor":"MTUwNTQ="}"#)
+                }
+                "/logstores/test/shards/0?count=10&cursor=MTUwNTQ%3D&type=log" => {
+                    // The service compresses with the requested codec
+                    let (_, encoding) = request
+                        .headers
+                        .iter()
+                        .find(|(name, _)| name == "accept-encoding")
+                        .unwrap();
+                    let compression = Compression::from_header_value(encoding).unwrap();
+                    HttpResponse::new(200)
+                        .with_header("x-log-cursor", "MTUwNTU=")
+                        .with_header("x-log-bodyrawsize", list.len().to_string())
+                        .with_header("x-log-compresstype", encoding)
+                        .with_body(compression.compress(list.clone()))
+                }
+                _ => HttpResponse::new(404),
+            }
+        });
+        let client = test_client().transport(transport).build().unwrap();
+        let target = Target::new("playground", "test");
+
+        let shards = client.list_shards(&target).await.unwrap();
+        assert_eq!(shards[0].status, ShardStatus::ReadWrite);
+        assert_eq!(shards[0].end_key, HashKey::new(1 << 127));
+
+        let cursor = client
+            .get_cursor(&target, 0, CursorPosition::Begin)
+            .await
+            .unwrap();
+        assert_eq!(cursor, Cursor::new("MTUwNTQ="));
+
+        let res = client.pull_logs(&target, 0, &cursor, 10).await.unwrap();
+        assert_eq!(res.next_cursor.as_str(), "MTUwNTU=");
+        assert_eq!(res.log_groups, [LogGroup::new(metadata, logs)]);
+    }
+
+    #[cfg(not(any(feature = "lz4", feature = "deflate", feature = "zstd")))]
+    #[tokio::test]
+    async fn test_pull_logs_without_codec() {
+        use crate::client::SlsClientError;
+
+        let transport = FakeTransport::new(|_| HttpResponse::new(200));
+        let client = test_client().transport(transport.clone()).build().unwrap();
+        let err = client
+            .pull_logs(
+                &Target::new("playground", "test"),
+                0,
+                &Cursor::new("MTUwNTQ="),
+                10,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlsClientError::Unsupported(_)));
+        assert!(transport.requests().is_empty());
+    }
+}
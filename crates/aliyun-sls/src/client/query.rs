@@ -0,0 +1,257 @@
+use crate::{
+    client::{SlsClient, SlsClientError, Target, headers, validate_target},
+    transport::{HttpResponse, Method},
+};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+/// Maximum number of logs returned by a GetLogs request.
+const MAX_LINE: u32 = 100;
+
+/// Parameters of a GetLogs request, querying logs of a logstore in a time range.
+///
+/// See <https://help.aliyun.com/zh/sls/developer-reference/api-sls-2020-12-30-getlogs>.
+///
+/// ```
+/// use aliyun_sls::GetLogs;
+///
+/// let request = GetLogs::new("level: ERROR", 1700000000, 1700003600)
+///     .with_line(50)
+///     .with_reverse(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetLogs {
+    query: String,
+    from: u32,
+    to: u32,
+    topic: Option<String>,
+    line: Option<u32>,
+    offset: Option<u32>,
+    reverse: bool,
+    power_sql: bool,
+}
+
+/// Result of a GetLogs request.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct GetLogsResponse<T> {
+    /// Logs or rows of the analytic result.
+    ///
+    /// Every value is returned as a string, e.g. `__time__` is `"1700000000"`.
+    pub logs: Vec<T>,
+    /// Whether the result is complete.
+    ///
+    /// An incomplete result should be requested again with the same parameters.
+    pub complete: bool,
+    /// Number of logs or rows returned.
+    pub count: u64,
+    /// Number of rows processed by the query.
+    pub processed_rows: u64,
+    /// Time spent on the query, in milliseconds.
+    pub elapsed_millis: u64,
+    /// Whether the query has an analytic statement.
+    pub has_sql: bool,
+}
+
+impl GetLogs {
+    /// Query logs in `from..to`, both UNIX timestamps in seconds.
+    ///
+    /// The query is a search statement, optionally followed by `| ` and an analytic statement.
+    pub fn new(query: impl Into<String>, from: u32, to: u32) -> Self {
+        Self {
+            query: query.into(),
+            from,
+            to,
+            topic: None,
+            line: None,
+            offset: None,
+            reverse: false,
+            power_sql: false,
+        }
+    }
+
+    /// Only query logs of the given topic.
+    pub fn with_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = Some(topic.into());
+        self
+    }
+
+    /// Set the maximum number of logs returned, clamped to `0..=100`.
+    ///
+    /// Defaults to 100. Ignored for analytic statements, which use `LIMIT` instead.
+    pub fn with_line(mut self, line: u32) -> Self {
+        self.line = Some(line.min(MAX_LINE));
+        self
+    }
+
+    /// Set the offset of the first log returned.
+    ///
+    /// Ignored for analytic statements, which use `LIMIT` instead.
+    pub fn with_offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Return the latest logs first.
+    pub fn with_reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Run analytic statements with the dedicated SQL resources.
+    pub fn with_power_sql(mut self, power_sql: bool) -> Self {
+        self.power_sql = power_sql;
+        self
+    }
+
+    /// Parameters of the next page, after the logs of this page.
+    pub fn next_page(mut self) -> Self {
+        let line = self.line.unwrap_or(MAX_LINE);
+        self.offset = Some(self.offset.unwrap_or(0).saturating_add(line));
+        self
+    }
+
+    fn to_query(&self) -> BTreeMap<String, String> {
+        let mut query = BTreeMap::from([
+            ("type".to_string(), "log".to_string()),
+            ("query".to_string(), self.query.clone()),
+            ("from".to_string(), self.from.to_string()),
+            ("to".to_string(), self.to.to_string()),
+            ("reverse".to_string(), self.reverse.to_string()),
+            ("powerSql".to_string(), self.power_sql.to_string()),
+        ]);
+        if let Some(topic) = &self.topic {
+            query.insert("topic".to_string(), topic.clone());
+        }
+        if let Some(line) = self.line {
+            query.insert("line".to_string(), line.to_string());
+        }
+        if let Some(offset) = self.offset {
+            query.insert("offset".to_string(), offset.to_string());
+        }
+        query
+    }
+}
+
+impl<T> GetLogsResponse<T> {
+    fn from_response(res: &HttpResponse, logs: Vec<T>) -> Self {
+        let number = |name| {
+            res.header(name)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default()
+        };
+        Self {
+            logs,
+            complete: res
+                .header(headers::LOG_PROGRESS)
+                .is_none_or(|progress| progress == "Complete"),
+            count: number(headers::LOG_COUNT),
+            processed_rows: number(headers::LOG_PROCESSED_ROWS),
+            elapsed_millis: number(headers::LOG_ELAPSED_MILLISECOND),
+            has_sql: res.header(headers::LOG_HAS_SQL) == Some("true"),
+        }
+    }
+}
+
+impl SlsClient {
+    /// Query logs of the default target of the client.
+    ///
+    /// Each log is deserialized from a JSON object of string values.
+    ///
+    /// Fails with [`SlsClientError::InvalidTarget`] if the client has no default target.
+    pub async fn get_logs<T: DeserializeOwned>(
+        &self,
+        request: &GetLogs,
+    ) -> Result<GetLogsResponse<T>, SlsClientError> {
+        let target = self.inner.target.as_ref().ok_or_else(|| {
+            SlsClientError::InvalidTarget("no default target, use `get_logs_from`".into())
+        })?;
+        self.get_logs_from(target, request).await
+    }
+
+    /// Query logs of the given target, its hash key is ignored.
+    ///
+    /// Each log is deserialized from a JSON object of string values.
+    pub async fn get_logs_from<T: DeserializeOwned>(
+        &self,
+        target: &Target,
+        request: &GetLogs,
+    ) -> Result<GetLogsResponse<T>, SlsClientError> {
+        validate_target(target)?;
+        let resource = format!("/logstores/{}", target.logstore());
+        let query = request.to_query();
+        let res = self
+            .retry("get_logs", true, || {
+                self.send(
+                    Method::Get,
+                    target.project(),
+                    &resource,
+                    &query,
+                    BTreeMap::new(),
+                    &[],
+                )
+            })
+            .await?;
+        let logs = serde_json::from_slice(&res.body)
+            .map_err(|e| SlsClientError::InvalidResponse(e.into()))?;
+        Ok(GetLogsResponse::from_response(&res, logs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        GetLogs,
+        test_utils::{FakeTransport, test_client},
+        transport::{HttpResponse, Method},
+    };
+
+    #[tokio::test]
+    async fn test_get_logs() {
+        #[derive(serde::Deserialize)]
+        struct Row {
+            #[serde(rename = "__time__")]
+            time: String,
+            message: String,
+        }
+
+        let transport = FakeTransport::new(|request| {
+            assert_eq!(request.method, Method::Get);
+            assert_eq!(
+                request.url,
+                "https://playground.cn-guangzhou.log.aliyuncs.com/logstores/test?\
+                 from=1700000000&line=2&offset=2&powerSql=false&query=level%3A%20ERROR&\
+                 reverse=true&to=1700003600&type=log"
+            );
+            HttpResponse::new(200)
+                .with_header("x-log-progress", "Incomplete")
+                .with_header("x-log-count", "1")
+                .with_body(r#"[{"__time__":"1700000001","level":"ERROR","message":"oops"}]"#)
+        });
+        let client = test_client()
+            .project("playground")
+            .logstore("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let request = GetLogs::new("level: ERROR", 1700000000, 1700003600)
+            .with_line(2)
+            .with_reverse(true)
+            .next_page();
+        let res = client.get_logs::<Row>(&request).await.unwrap();
+        assert!(!res.complete);
+        assert_eq!(res.count, 1);
+        assert_eq!(res.logs[0].time, "1700000001");
+        assert_eq!(res.logs[0].message, "oops");
+    }
+
+    #[test]
+    fn test_get_logs_paging() {
+        let request = GetLogs::new("*", 1700000000, 1700003600).with_line(500);
+        assert_eq!(request.to_query()["line"], "100");
+
+        let request = request.with_offset(u32::MAX - 1).next_page();
+        assert_eq!(request.to_query()["offset"], u32::MAX.to_string());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    client::{ErrorCode, SlsClientError},
+    transport::TransportError,
+};
+use std::{fmt, pin::Pin, sync::Arc, time::Duration};
+
+/// Trait for creating the delay future between two attempts.
+pub trait RetryTimer: Send + Sync + 'static {
+    /// Create a future that completes after `duration`.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
+}
+
+/// Randomization applied to the exponential backoff delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// Use the exponential delay as is.
+    None,
+    /// Pick a random delay between zero and the exponential delay.
+    #[default]
+    Full,
+    /// Keep half of the exponential delay and randomize the other half.
+    Equal,
+}
+
+/// Policy for retrying failed requests with exponential backoff.
+///
+/// A request is retried when it fails with one of the retryable status codes or error codes,
+/// or with a transport error of a retryable kind, until `max_attempts` is reached.
+/// Each attempt is signed again, so the `Date` header stays fresh.
+///
+/// Only PutLogs and idempotent requests are retried by default. Other requests may have been
+/// applied before failing, see [`RetryPolicy::with_retry_non_idempotent`].
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: Jitter,
+    retryable_status: Vec<u16>,
+    retryable_error_codes: Vec<ErrorCode>,
+    retry_on_timeout: bool,
+    retry_on_connect: bool,
+    retry_non_idempotent: bool,
+    timer: Arc<dyn RetryTimer>,
+    #[cfg(feature = "blocking")]
+    blocking: bool,
+}
+
+impl RetryPolicy {
+    /// Create a new retry policy using the given timer for delays.
+    ///
+    /// Defaults to 3 attempts, 100ms base delay, 10s max delay, [`Jitter::Full`],
+    /// retrying on status `429`, `500`, `502`, `503`, `504`, error codes `WriteQuotaExceed`,
+    /// `ShardWriteQuotaExceed`, `ServerBusy`, timeouts and connect errors,
+    /// only for idempotent requests.
+    pub fn new(timer: impl RetryTimer) -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            jitter: Jitter::default(),
+            retryable_status: vec![429, 500, 502, 503, 504],
+            retryable_error_codes: vec![
+                ErrorCode::WriteQuotaExceed,
+                ErrorCode::ShardWriteQuotaExceed,
+                ErrorCode::ServerBusy,
+            ],
+            retry_on_timeout: true,
+            retry_on_connect: true,
+            retry_non_idempotent: false,
+            timer: Arc::new(timer),
+            #[cfg(feature = "blocking")]
+            blocking: false,
+        }
+    }
+
+    /// Create a new retry policy sleeping the current thread between attempts,
+    /// as needed by [`BlockingSlsClient`](crate::BlockingSlsClient).
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+    pub fn blocking() -> Self {
+        Self {
+            blocking: true,
+            ..Self::new(|duration| async move { std::thread::sleep(duration) })
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    ///
+    /// `1` disables retrying.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry, doubled on each following retry.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the upper bound of the delay between two attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Set the jitter applied to the delay.
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the HTTP status codes that are retried.
+    pub fn with_retryable_status(mut self, status: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_status = status.into_iter().collect();
+        self
+    }
+
+    /// Set the SLS error codes that are retried regardless of the status code.
+    pub fn with_retryable_error_codes(
+        mut self,
+        codes: impl IntoIterator<Item = ErrorCode>,
+    ) -> Self {
+        self.retryable_error_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// Set whether requests that timed out are retried.
+    pub fn with_retry_on_timeout(mut self, retry_on_timeout: bool) -> Self {
+        self.retry_on_timeout = retry_on_timeout;
+        self
+    }
+
+    /// Set whether requests that failed to connect are retried.
+    pub fn with_retry_on_connect(mut self, retry_on_connect: bool) -> Self {
+        self.retry_on_connect = retry_on_connect;
+        self
+    }
+
+    /// Set whether non-idempotent requests are retried.
+    ///
+    /// Default is `false`, as the request may have been applied before failing.
+    pub fn with_retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Returns the delay before the next attempt, or `None` if `err` should not be retried.
+    ///
+    /// `attempt` is the number of attempts made so far.
+    pub(crate) fn next_delay(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        err: &SlsClientError,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts
+            || !(idempotent || self.retry_non_idempotent)
+            || !self.is_retryable(err)
+        {
+            return None;
+        }
+        Some(self.backoff(attempt))
+    }
+
+    /// Whether the policy was created with [`RetryPolicy::blocking`].
+    #[cfg(feature = "blocking")]
+    pub(crate) fn is_blocking(&self) -> bool {
+        self.blocking
+    }
+
+    pub(crate) async fn sleep(&self, duration: Duration) {
+        self.timer.sleep(duration).await
+    }
+
+    fn is_retryable(&self, err: &SlsClientError) -> bool {
+        match err {
+            SlsClientError::Http { status, code, .. } => {
+                self.retryable_status.contains(status)
+                    || code
+                        .as_ref()
+                        .is_some_and(|code| self.retryable_error_codes.contains(code))
+            }
+            SlsClientError::Transport(TransportError::Timeout(_)) => self.retry_on_timeout,
+            SlsClientError::Transport(TransportError::Connect(_)) => self.retry_on_connect,
+            SlsClientError::Transport(_) => false,
+            SlsClientError::InvalidTarget(_)
+            | SlsClientError::InvalidArgument(_)
+            | SlsClientError::Credentials(_)
+            | SlsClientError::Unsupported(_)
+            | SlsClientError::InvalidResponse(_)
+            | SlsClientError::PartialFailure { .. } => false,
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        match self.jitter {
+            Jitter::None => delay,
+            Jitter::Full => delay.mul_f64(fastrand::f64()),
+            Jitter::Equal => delay / 2 + (delay / 2).mul_f64(fastrand::f64()),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("retryable_status", &self.retryable_status)
+            .field("retryable_error_codes", &self.retryable_error_codes)
+            .field("retry_on_timeout", &self.retry_on_timeout)
+            .field("retry_on_connect", &self.retry_on_connect)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut> RetryTimer for F
+where
+    F: Fn(Duration) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + Sync + 'static,
+{
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + Sync>> {
+        Box::pin(self(duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(|_| async {})
+            .with_max_attempts(10)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1))
+            .with_jitter(Jitter::None);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(64), Duration::from_secs(1));
+
+        let policy = policy.with_jitter(Jitter::Equal);
+        for attempt in 1..10 {
+            let delay = policy.backoff(attempt);
+            assert!(delay <= Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let policy = RetryPolicy::new(|_| async {})
+            .with_max_attempts(3)
+            .with_jitter(Jitter::None);
+        let http = |status, body: &str| SlsClientError::from_response(status, None, body.into());
+
+        let busy = http(503, "");
+        assert_eq!(
+            policy.next_delay(1, true, &busy),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.next_delay(2, true, &busy),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.next_delay(3, true, &busy), None);
+        assert_eq!(policy.next_delay(1, true, &http(400, "")), None);
+
+        assert_eq!(policy.next_delay(1, false, &busy), None);
+        let policy = policy.with_retry_non_idempotent(true);
+        assert_eq!(
+            policy.next_delay(1, false, &busy),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let policy = RetryPolicy::new(|_| async {});
+        let http = |status, body: &str| SlsClientError::from_response(status, None, body.into());
+
+        assert!(policy.is_retryable(&http(500, "")));
+        assert!(policy.is_retryable(&http(429, "")));
+        assert!(!policy.is_retryable(&http(400, "")));
+        assert!(policy.is_retryable(&http(
+            403,
+            r#"{"errorCode":"WriteQuotaExceed","errorMessage":"quota"}"#
+        )));
+        assert!(!policy.is_retryable(&http(
+            403,
+            r#"{"errorCode":"Unauthorized","errorMessage":"denied"}"#
+        )));
+
+        let timeout = || SlsClientError::Transport(TransportError::timeout("timed out"));
+        let connect = SlsClientError::Transport(TransportError::connect("refused"));
+        assert!(policy.is_retryable(&timeout()));
+        assert!(policy.is_retryable(&connect));
+        assert!(!policy.is_retryable(&SlsClientError::Transport(TransportError::other("tls"))));
+
+        let policy = policy.with_retry_on_timeout(false);
+        assert!(!policy.is_retryable(&timeout()));
+        assert!(policy.is_retryable(&connect));
+
+        let policy = policy.with_retryable_status([400]);
+        assert!(policy.is_retryable(&http(400, "")));
+        assert!(!policy.is_retryable(&http(500, "")));
+    }
+}
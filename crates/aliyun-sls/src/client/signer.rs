@@ -1,88 +1,374 @@
-use crate::client::headers;
+use crate::{client::headers, credentials::Credentials, transport::Method};
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use hmac::{Hmac, Mac};
 use jiff::Timestamp;
 use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt::Write};
+
+const V4_ALGORITHM: &str = "SLS4-HMAC-SHA256";
+const V4_PRODUCT: &str = "sls";
+const V4_REQUEST: &str = "aliyun_v4_request";
+
+/// Version of the signature used to authenticate requests.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureVersion {
+    /// Legacy `LOG <access key id>:<signature>` scheme, signed with HMAC-SHA1.
+    #[default]
+    V1,
+    /// `SLS4-HMAC-SHA256` scheme, scoped to a region.
+    ///
+    /// Requires [`SlsClientBuilder::region`](crate::SlsClientBuilder::region).
+    V4,
+}
 
 pub(super) struct Signer {
-    pub(super) hmac: Hmac<Sha1>,
-    pub(super) access_key: String,
-    pub(super) canonicalized_resource: String,
+    pub(super) version: SignatureVersion,
+    pub(super) region: Option<String>,
 }
 
-pub(super) struct Signature {
-    pub(super) date: String,
-    pub(super) raw_length: String,
-    pub(super) content_md5: String,
-    pub(super) authorization: String,
+/// A request to be signed.
+///
+/// Header names are lowercase. Signing adds the date, digest and authorization headers
+/// to `headers`, which must then be sent as is.
+pub(super) struct SignRequest<'a> {
+    pub(super) method: Method,
+    pub(super) resource: &'a str,
+    pub(super) query: &'a BTreeMap<String, String>,
+    pub(super) headers: &'a mut BTreeMap<String, String>,
+    pub(super) body: &'a [u8],
 }
 
 impl Signer {
-    pub fn sign(&self, encoded_len: usize, encoded: &[u8]) -> Signature {
-        let mut mac = self.hmac.clone();
-
-        let date = Timestamp::now()
-            .strftime("%a, %d %b %Y %H:%M:%S GMT")
-            .to_string();
-        let raw_length = encoded_len.to_string();
-        let content_md5 = hex::encode_upper(md5::compute(encoded).as_ref());
-
-        // SignString = VERB + "\n"
-        //     + CONTENT-MD5 + "\n"
-        //     + CONTENT-TYPE + "\n"
-        //     + DATE + "\n"
-        //     + CanonicalizedLOGHeaders + "\n"
-        //     + CanonicalizedResource
-        mac.update(b"POST\n");
-        mac.update(content_md5.as_bytes());
-        mac.update(b"\n");
+    pub fn sign(&self, credentials: &Credentials, request: SignRequest<'_>) {
+        self.sign_at(credentials, request, Timestamp::now())
+    }
 
-        mac.update(headers::DEFAULT_CONTENT_TYPE.as_bytes());
-        mac.update(b"\n");
+    fn sign_at(&self, credentials: &Credentials, request: SignRequest<'_>, now: Timestamp) {
+        request.headers.insert(
+            headers::LOG_API_VERSION.to_string(),
+            headers::API_VERSION.to_string(),
+        );
+        if let Some(security_token) = credentials.security_token() {
+            request.headers.insert(
+                headers::ACS_SECURITY_TOKEN.to_string(),
+                security_token.to_string(),
+            );
+        }
+        match self.version {
+            SignatureVersion::V1 => sign_v1(credentials, request, now),
+            SignatureVersion::V4 => {
+                let region = self.region.as_deref().expect("checked by the builder");
+                sign_v4(credentials, region, request, now)
+            }
+        }
+    }
+}
 
-        mac.update(date.as_bytes());
-        mac.update(b"\n");
+fn sign_v1(credentials: &Credentials, request: SignRequest<'_>, now: Timestamp) {
+    let headers = request.headers;
+    headers.insert(
+        headers::DATE.to_string(),
+        now.strftime("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+    );
+    headers.insert(
+        headers::LOG_SIGNATURE_METHOD.to_string(),
+        headers::SIGNATURE_METHOD.to_string(),
+    );
+    if !request.body.is_empty() {
+        headers.insert(
+            headers::CONTENT_MD5.to_string(),
+            hex::encode_upper(md5::compute(request.body).as_ref()),
+        );
+    }
 
-        // CanonicalizedLOGHeaders的构造方式如下：
-        // 将所有以x-log和x-acs为前缀的HTTP请求头的名字转换成小写字母。
-        // 将上一步得到的所有LOG自定义请求头按照字典顺序进行升序排序。
-        // 删除请求头和内容之间分隔符两端出现的任何空格。
-        // 将所有的头和内容用\n分隔符组合成最后的CanonicalizedLOGHeader。
-        mac.update(headers::LOG_API_VERSION.as_bytes());
-        mac.update(b":");
-        mac.update(headers::API_VERSION.as_bytes());
-        mac.update(b"\n");
-        mac.update(headers::LOG_BODY_RAW_SIZE.as_bytes());
-        mac.update(b":");
-        mac.update(raw_length.as_bytes());
-        #[cfg(not(any(feature = "lz4", feature = "deflate")))]
+    let mut mac = Hmac::<Sha1>::new_from_slice(credentials.access_key_secret().as_bytes())
+        .expect("HMAC can take key of any size");
+
+    // SignString = VERB + "\n"
+    //     + CONTENT-MD5 + "\n"
+    //     + CONTENT-TYPE + "\n"
+    //     + DATE + "\n"
+    //     + CanonicalizedLOGHeaders + "\n"
+    //     + CanonicalizedResource
+    mac.update(request.method.as_str().as_bytes());
+    mac.update(b"\n");
+    for name in [headers::CONTENT_MD5, headers::CONTENT_TYPE, headers::DATE] {
+        if let Some(value) = headers.get(name) {
+            mac.update(value.as_bytes());
+        }
         mac.update(b"\n");
-        #[cfg(feature = "lz4")]
-        mac.update(b"\nx-log-compresstype:lz4\n");
-        #[cfg(feature = "deflate")]
-        mac.update(b"\nx-log-compresstype:deflate\n");
-        mac.update(headers::LOG_SIGNATURE_METHOD.as_bytes());
+    }
+
+    // CanonicalizedLOGHeaders的构造方式如下：
+    // 将所有以x-log和x-acs为前缀的HTTP请求头的名字转换成小写字母。
+    // 将上一步得到的所有LOG自定义请求头按照字典顺序进行升序排序。
+    // 删除请求头和内容之间分隔符两端出现的任何空格。
+    // 将所有的头和内容用\n分隔符组合成最后的CanonicalizedLOGHeader。
+    for (name, value) in headers
+        .iter()
+        .filter(|(name, _)| name.starts_with("x-log-") || name.starts_with("x-acs-"))
+    {
+        mac.update(name.as_bytes());
         mac.update(b":");
-        mac.update(headers::SIGNATURE_METHOD.as_bytes());
+        mac.update(value.trim().as_bytes());
         mac.update(b"\n");
+    }
 
-        // CanonicalizedResource的构造方式如下：
-        // a. 将CanonicalizedResource设置为空字符串" "。
-        // b. 放入要访问的LOG资源，如/logstores/logstorename（如果没有logstorename则可不填写）。
-        // c. 如果请求包含查询字符串QUERY_STRING，则在CanonicalizedResource字符串尾部添加?和查询字符串。
-        //
-        // QUERY_STRING是URL中请求参数按字典顺序排序后的字符串，其中参数名和值之间用=相隔组成字符串，并对参数名-值对按照字典顺序升序排序，然后以&符号连接构成字符串。其公式化描述如下：
-        // QUERY_STRING = "KEY1=VALUE1" + "&" + "KEY2=VALUE2"
-        mac.update(self.canonicalized_resource.as_bytes());
-        let authorization = BASE64_STANDARD.encode(mac.finalize().into_bytes());
-        let authorization = format!("LOG {}:{}", self.access_key, authorization);
-
-        Signature {
-            date,
-            raw_length,
-            content_md5,
-            authorization,
+    // CanonicalizedResource的构造方式如下：
+    // a. 将CanonicalizedResource设置为空字符串" "。
+    // b. 放入要访问的LOG资源，如/logstores/logstorename（如果没有logstorename则可不填写）。
+    // c. 如果请求包含查询字符串QUERY_STRING，则在CanonicalizedResource字符串尾部添加?和查询字符串。
+    //
+    // QUERY_STRING是URL中请求参数按字典顺序排序后的字符串，其中参数名和值之间用=相隔组成字符串，并对参数名-值对按照字典顺序升序排序，然后以&符号连接构成字符串。其公式化描述如下：
+    // QUERY_STRING = "KEY1=VALUE1" + "&" + "KEY2=VALUE2"
+    mac.update(request.resource.as_bytes());
+    for (i, (key, value)) in request.query.iter().enumerate() {
+        mac.update(if i == 0 { b"?" } else { b"&" });
+        mac.update(key.as_bytes());
+        mac.update(b"=");
+        mac.update(value.as_bytes());
+    }
+    let signature = BASE64_STANDARD.encode(mac.finalize().into_bytes());
+    headers.insert(
+        headers::AUTHORIZATION.to_string(),
+        format!("LOG {}:{signature}", credentials.access_key_id()),
+    );
+}
+
+fn sign_v4(credentials: &Credentials, region: &str, request: SignRequest<'_>, now: Timestamp) {
+    let date_time = now.strftime("%Y%m%dT%H%M%SZ").to_string();
+    let date = &date_time[..8];
+    let content_sha256 = hex::encode(Sha256::digest(request.body));
+    request
+        .headers
+        .insert(headers::LOG_DATE.to_string(), date_time.clone());
+    request.headers.insert(
+        headers::LOG_CONTENT_SHA256.to_string(),
+        content_sha256.clone(),
+    );
+    let canonical_request = canonical_request_v4(&request, &content_sha256);
+
+    // StringToSign = Algorithm + "\n" + DateTime + "\n" + Scope + "\n" + Hex(SHA256(CanonicalRequest))
+    let scope = format!("{date}/{region}/{V4_PRODUCT}/{V4_REQUEST}");
+    let string_to_sign = format!(
+        "{V4_ALGORITHM}\n{date_time}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    // The signing key is derived from the secret, and scoped to the date, region and product
+    let signing_key = [date, region, V4_PRODUCT, V4_REQUEST].into_iter().fold(
+        format!("aliyun_v4{}", credentials.access_key_secret()).into_bytes(),
+        |key, data| hmac_sha256(&key, data.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+    request.headers.insert(
+        headers::AUTHORIZATION.to_string(),
+        format!(
+            "{V4_ALGORITHM} Credential={}/{scope},Signature={signature}",
+            credentials.access_key_id()
+        ),
+    );
+}
+
+/// Canonical request of the V4 signature, once the date and digest headers are set.
+fn canonical_request_v4(request: &SignRequest<'_>, content_sha256: &str) -> String {
+    // CanonicalRequest = METHOD + "\n"
+    //     + CanonicalURI + "\n"
+    //     + CanonicalQueryString + "\n"
+    //     + CanonicalHeaders + "\n"
+    //     + SignedHeaders + "\n"
+    //     + HashedPayload
+    let mut canonical_headers = String::new();
+    let mut signed_headers = Vec::new();
+    for (name, value) in request.headers.iter().filter(|(name, _)| {
+        matches!(
+            name.as_str(),
+            headers::CONTENT_TYPE | headers::CONTENT_MD5 | headers::HOST
+        ) || name.starts_with("x-log-")
+            || name.starts_with("x-acs-")
+    }) {
+        writeln!(canonical_headers, "{name}:{}", value.trim()).expect("infallible");
+        signed_headers.push(name.as_str());
+    }
+    format!(
+        "{}\n{}\n{}\n{canonical_headers}\n{}\n{content_sha256}",
+        request.method,
+        encode_path(request.resource),
+        canonicalized_query(request.query),
+        signed_headers.join(";"),
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode each segment of a resource path.
+pub(crate) fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(percent_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Sign a RPC style request, as used by STS.
+///
+/// Returns the `Signature` parameter for `params`, which must contain all other parameters.
+pub(crate) fn sign_rpc(
+    method: &str,
+    params: &BTreeMap<&str, String>,
+    access_key_secret: &str,
+) -> String {
+    // StringToSign = HTTPMethod + "&" + percentEncode("/") + "&" + percentEncode(CanonicalizedQueryString)
+    let string_to_sign = format!(
+        "{method}&{}&{}",
+        percent_encode("/"),
+        percent_encode(&canonicalized_query(params))
+    );
+    // The key is the access key secret followed by "&"
+    let mut mac = Hmac::<Sha1>::new_from_slice(format!("{access_key_secret}&").as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(string_to_sign.as_bytes());
+    BASE64_STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// Percent-encode, only the unreserved characters of RFC 3986 are kept.
+pub(crate) fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => write!(encoded, "%{b:02X}").expect("infallible"),
         }
     }
+    encoded
+}
+
+/// Sorted and percent-encoded query string.
+pub(crate) fn canonicalized_query<K: AsRef<str>>(params: &BTreeMap<K, String>) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", percent_encode(key.as_ref()), percent_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("abc-_.~XYZ019"), "abc-_.~XYZ019");
+        assert_eq!(percent_encode("a b*c+d/e"), "a%20b%2Ac%2Bd%2Fe");
+        assert_eq!(
+            percent_encode("2025-01-01T00:00:00Z"),
+            "2025-01-01T00%3A00%3A00Z"
+        );
+        assert_eq!(percent_encode("中"), "%E4%B8%AD");
+    }
+
+    #[test]
+    fn test_sign_rpc() {
+        // Example from https://help.aliyun.com/zh/sdk/product-overview/rpc-mechanism
+        let params = BTreeMap::from([
+            ("AccessKeyId", "testid".to_string()),
+            ("Action", "DescribeRegions".to_string()),
+            ("Format", "XML".to_string()),
+            ("SignatureMethod", "HMAC-SHA1".to_string()),
+            (
+                "SignatureNonce",
+                "3ee8c1b8-83d3-44af-a94f-4e0ad82fd6cf".to_string(),
+            ),
+            ("SignatureVersion", "1.0".to_string()),
+            ("Timestamp", "2016-02-23T12:46:24Z".to_string()),
+            ("Version", "2014-05-26".to_string()),
+        ]);
+        assert_eq!(
+            sign_rpc("GET", &params, "testsecret"),
+            "OLeaidS1JvxuMvnyHOwuJ+uX5qY="
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sign_v4() {
+        // The body digest is the SHA-256 example of FIPS 180-2, and the canonical request follows
+        // the SLS4-HMAC-SHA256 specification. The signature guards against regressions only.
+        let signer = Signer {
+            version: SignatureVersion::V4,
+            region: Some("cn-hangzhou".to_string()),
+        };
+        let query = BTreeMap::from([("key".to_string(), "a b".to_string())]);
+        let mut headers = BTreeMap::from([
+            (
+                headers::HOST.to_string(),
+                "test.cn-hangzhou.log.aliyuncs.com".to_string(),
+            ),
+            (
+                headers::CONTENT_TYPE.to_string(),
+                headers::DEFAULT_CONTENT_TYPE.to_string(),
+            ),
+            (headers::LOG_BODY_RAW_SIZE.to_string(), "3".to_string()),
+        ]);
+        signer.sign_at(
+            &Credentials::new("id", "secret"),
+            SignRequest {
+                method: Method::Post,
+                resource: "/logstores/test/shards/route",
+                query: &query,
+                headers: &mut headers,
+                body: b"abc",
+            },
+            "2025-01-01T00:00:00Z".parse().unwrap(),
+        );
+        assert_eq!(headers[headers::LOG_DATE], "20250101T000000Z");
+        let authorization = headers.remove(headers::AUTHORIZATION).unwrap();
+
+        let content_sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            canonical_request_v4(
+                &SignRequest {
+                    method: Method::Post,
+                    resource: "/logstores/test/shards/route",
+                    query: &query,
+                    headers: &mut headers,
+                    body: b"abc",
+                },
+                content_sha256
+            ),
+            "POST\n\
+             /logstores/test/shards/route\n\
+             key=a%20b\n\
+             content-type:application/x-protobuf\n\
+             host:test.cn-hangzhou.log.aliyuncs.com\n\
+             x-log-apiversion:0.6.0\n\
+             x-log-bodyrawsize:3\n\
+             x-log-content-sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n\
+             x-log-date:20250101T000000Z\n\
+             \n\
+             content-type;host;x-log-apiversion;x-log-bodyrawsize;x-log-content-sha256;x-log-date\n\
+             ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            authorization,
+            "SLS4-HMAC-SHA256 Credential=id/20250101/cn-hangzhou/sls/aliyun_v4_request,\
+             Signature=93c64b77f7c19f8deced45dfb090a1ed52b6eb49bd56e6980f2a679748ac242d"
+        );
+    }
 }
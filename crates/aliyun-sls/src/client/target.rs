@@ -0,0 +1,48 @@
+use crate::client::HashKey;
+
+/// Project and logstore logs are written to.
+///
+/// ```
+/// use aliyun_sls::{HashKey, Target};
+///
+/// let audit = Target::new("my-project", "audit-log");
+/// let access = Target::new("my-project", "access-log").with_hash_key(HashKey::new(1 << 127));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Target {
+    project: String,
+    logstore: String,
+    hash_key: Option<HashKey>,
+}
+
+impl Target {
+    /// Create a target writing to `logstore` of `project`, load-balanced across shards.
+    pub fn new(project: impl Into<String>, logstore: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            logstore: logstore.into(),
+            hash_key: None,
+        }
+    }
+
+    /// Write to the shard whose hash range contains `hash_key`, instead of any writable shard.
+    pub fn with_hash_key(mut self, hash_key: HashKey) -> Self {
+        self.hash_key = Some(hash_key);
+        self
+    }
+
+    /// Project name.
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Logstore name.
+    pub fn logstore(&self) -> &str {
+        &self.logstore
+    }
+
+    /// Hash key used to route to a shard, if any.
+    pub fn hash_key(&self) -> Option<HashKey> {
+        self.hash_key
+    }
+}
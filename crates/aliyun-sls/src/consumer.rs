@@ -0,0 +1,503 @@
+//! A worker consuming a logstore as a member of a consumer group.
+//!
+//! Consumers of the same group share the shards of the logstore: each shard is assigned to
+//! one consumer through heartbeats, and its progress is committed as a checkpoint,
+//! so another consumer can take over where it stopped.
+use crate::{
+    ConsumerGroup, Cursor, CursorPosition, ErrorCode, LogGroup, RetryTimer, SlsClient,
+    SlsClientError, Target,
+};
+use futures_util::{
+    FutureExt,
+    future::{self, Either},
+};
+use std::{
+    collections::BTreeMap,
+    future::pending,
+    pin::{Pin, pin},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const HEARTBEAT_DEFAULT_INTERVAL: Duration = Duration::from_secs(20);
+const FETCH_DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+const FETCH_DEFAULT_COUNT: u32 = 1000;
+
+/// Future returned by [`LogGroupProcessor::process`].
+pub type ProcessFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Trait for processing the log groups pulled by a [`ConsumerWorker`].
+pub trait LogGroupProcessor: Send + 'static {
+    /// Process the log groups pulled from `shard`.
+    ///
+    /// The checkpoint is committed once the future completes,
+    /// logs are processed at least once.
+    fn process(&mut self, shard: u32, log_groups: Vec<LogGroup>) -> ProcessFuture<'_>;
+}
+
+/// Consuming progress of a held shard.
+#[derive(Default)]
+struct ShardProgress {
+    /// Cursor to pull from, `None` until the shard is first pulled.
+    cursor: Option<Cursor>,
+    /// Whether the logs before `cursor` are processed, but its checkpoint is not committed.
+    uncommitted: bool,
+}
+
+/// A worker consuming the shards assigned to it in a consumer group.
+pub struct ConsumerWorker<P> {
+    inner: WorkerInner,
+    processor: P,
+    shutdown_signal: Pin<Box<dyn Future<Output = ()> + Send + Sync>>,
+}
+
+/// Settings of a worker, shared by its heartbeat and fetch loops.
+struct WorkerInner {
+    client: SlsClient,
+    target: Target,
+    group: ConsumerGroup,
+    consumer: String,
+    timer: Arc<dyn RetryTimer>,
+    position: CursorPosition,
+    heartbeat_interval: Duration,
+    fetch_interval: Duration,
+    fetch_count: u32,
+}
+
+impl<P: LogGroupProcessor> ConsumerWorker<P> {
+    /// Create a worker named `consumer` in the consumer group of the logstore of `target`.
+    ///
+    /// The timer is used to wait between heartbeats and pulls, e.g. `|d| tokio::time::sleep(d)`.
+    pub fn new(
+        client: SlsClient,
+        target: Target,
+        group: ConsumerGroup,
+        consumer: impl Into<String>,
+        processor: P,
+        timer: impl RetryTimer,
+    ) -> Self {
+        Self {
+            inner: WorkerInner {
+                client,
+                target,
+                group,
+                consumer: consumer.into(),
+                timer: Arc::new(timer),
+                position: CursorPosition::Begin,
+                heartbeat_interval: HEARTBEAT_DEFAULT_INTERVAL,
+                fetch_interval: FETCH_DEFAULT_INTERVAL,
+                fetch_count: FETCH_DEFAULT_COUNT,
+            },
+            processor,
+            shutdown_signal: Box::pin(pending()),
+        }
+    }
+
+    /// Set where to start consuming shards without checkpoint.
+    ///
+    /// Default is [`CursorPosition::Begin`].
+    pub fn with_position(mut self, position: CursorPosition) -> Self {
+        self.inner.position = position;
+        self
+    }
+
+    /// Set the interval of heartbeats, which must be well below the timeout of the group.
+    ///
+    /// Heartbeats are sent on their own timer, also while log groups are being processed.
+    ///
+    /// Default is 20 seconds.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.inner.heartbeat_interval = interval;
+        self
+    }
+
+    /// Set how long to wait before pulling again when no shard has new logs.
+    ///
+    /// Default is 1 second.
+    pub fn with_fetch_interval(mut self, interval: Duration) -> Self {
+        self.inner.fetch_interval = interval;
+        self
+    }
+
+    /// Set the maximum number of log groups pulled from a shard at once, clamped to `1..=1000`.
+    ///
+    /// Default is `1000`.
+    pub fn with_fetch_count(mut self, count: u32) -> Self {
+        self.inner.fetch_count = count.clamp(1, FETCH_DEFAULT_COUNT);
+        self
+    }
+
+    /// Quit when shutdown_signal received.
+    ///
+    /// Accept a `shutdown_signal` argument as a graceful shutdown signal.
+    pub fn with_graceful_shutdown(
+        mut self,
+        shutdown_signal: impl Future<Output = ()> + Send + Sync + 'static,
+    ) -> Self {
+        self.shutdown_signal = Box::pin(shutdown_signal);
+        self
+    }
+
+    /// Run the worker until the shutdown signal is received.
+    ///
+    /// The consumer group is created, or updated if it exists. Heartbeats are sent
+    /// concurrently with the pulls, so slow processing doesn't let the shards time out.
+    /// Errors of heartbeats and pulls are logged if tracing is enabled on the client,
+    /// and retried on the next round, only failing to set up the consumer group stops the worker.
+    pub async fn run(self) -> Result<(), SlsClientError> {
+        let Self {
+            inner,
+            mut processor,
+            shutdown_signal,
+        } = self;
+        match inner
+            .client
+            .create_consumer_group(&inner.target, &inner.group)
+            .await
+        {
+            Err(e) if e.code() == Some(&ErrorCode::ConsumerGroupAlreadyExist) => {
+                inner
+                    .client
+                    .update_consumer_group(&inner.target, &inner.group)
+                    .await?
+            }
+            result => result?,
+        }
+
+        // Shards assigned by the last successful heartbeat
+        let assigned = Mutex::new(Vec::new());
+        let heartbeat = pin!(inner.heartbeat_loop(&assigned));
+        let fetch = pin!(inner.fetch_loop(&mut processor, &assigned, shutdown_signal));
+        // The heartbeat loop never completes, only the fetch loop stops on shutdown
+        future::select(heartbeat, fetch).await;
+
+        // Checkpoints are committed by the fetch loop, the shards can be released right away
+        if let Err(e) = inner
+            .client
+            .heartbeat(&inner.target, inner.group.name(), &inner.consumer, &[])
+            .await
+        {
+            if inner.client.enable_trace() {
+                tracing::warn!(err = ?e, "failed to release shards");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WorkerInner {
+    /// Send heartbeats every interval, updating the assigned shards.
+    async fn heartbeat_loop(&self, assigned: &Mutex<Vec<u32>>) {
+        loop {
+            let held = assigned.lock().unwrap().clone();
+            match self
+                .client
+                .heartbeat(&self.target, self.group.name(), &self.consumer, &held)
+                .await
+            {
+                Ok(shards) => *assigned.lock().unwrap() = shards,
+                Err(e) => {
+                    if self.client.enable_trace() {
+                        tracing::warn!(err = ?e, "failed to send heartbeat");
+                    }
+                }
+            }
+            self.timer.sleep(self.heartbeat_interval).await;
+        }
+    }
+
+    /// Pull and process the assigned shards until the shutdown signal is received.
+    async fn fetch_loop<P: LogGroupProcessor>(
+        &self,
+        processor: &mut P,
+        assigned: &Mutex<Vec<u32>>,
+        mut shutdown_signal: Pin<Box<dyn Future<Output = ()> + Send + Sync>>,
+    ) {
+        let mut shards = BTreeMap::<u32, ShardProgress>::new();
+        loop {
+            {
+                let assigned = assigned.lock().unwrap();
+                shards.retain(|shard, _| assigned.contains(shard));
+                for &shard in assigned.iter() {
+                    shards.entry(shard).or_default();
+                }
+            }
+
+            let mut idle = true;
+            for (&shard, progress) in shards.iter_mut() {
+                match self.fetch(processor, shard, progress).await {
+                    Ok(fetched) => idle &= !fetched,
+                    Err(e) => {
+                        if self.client.enable_trace() {
+                            tracing::warn!(shard, err = ?e, "failed to consume shard");
+                        }
+                    }
+                }
+            }
+
+            let shutdown = if idle {
+                let sleep = self.timer.sleep(self.fetch_interval);
+                let shutdown = future::select(sleep, shutdown_signal.as_mut()).await;
+                matches!(shutdown, Either::Right(_))
+            } else {
+                shutdown_signal.as_mut().now_or_never().is_some()
+            };
+            if shutdown {
+                // Last chance to commit the checkpoints which failed
+                for (&shard, progress) in shards.iter_mut() {
+                    if let Err(e) = self.commit(shard, progress).await {
+                        if self.client.enable_trace() {
+                            tracing::warn!(shard, err = ?e, "failed to commit checkpoint");
+                        }
+                    }
+                }
+                return;
+            }
+        }
+    }
+
+    /// Pull and process logs of a shard, returning whether there were any.
+    ///
+    /// A checkpoint which failed to be committed is retried before pulling,
+    /// so processed logs are not pulled again.
+    async fn fetch<P: LogGroupProcessor>(
+        &self,
+        processor: &mut P,
+        shard: u32,
+        progress: &mut ShardProgress,
+    ) -> Result<bool, SlsClientError> {
+        self.commit(shard, progress).await?;
+        let current = match &progress.cursor {
+            Some(cursor) => cursor.clone(),
+            None => self.start_cursor(shard).await?,
+        };
+        let res = self
+            .client
+            .pull_logs(&self.target, shard, &current, self.fetch_count)
+            .await?;
+        if res.log_groups.is_empty() {
+            progress.cursor = Some(res.next_cursor);
+            return Ok(false);
+        }
+
+        processor.process(shard, res.log_groups).await;
+        progress.cursor = Some(res.next_cursor);
+        progress.uncommitted = true;
+        self.commit(shard, progress).await?;
+        Ok(true)
+    }
+
+    /// Commit the checkpoint of the shard, if it is not committed yet.
+    async fn commit(&self, shard: u32, progress: &mut ShardProgress) -> Result<(), SlsClientError> {
+        let (true, Some(cursor)) = (progress.uncommitted, &progress.cursor) else {
+            return Ok(());
+        };
+        self.client
+            .update_checkpoint(
+                &self.target,
+                self.group.name(),
+                &self.consumer,
+                shard,
+                cursor,
+            )
+            .await?;
+        progress.uncommitted = false;
+        Ok(())
+    }
+
+    /// Cursor of the checkpoint of the shard, or of the start position without checkpoint.
+    async fn start_cursor(&self, shard: u32) -> Result<Cursor, SlsClientError> {
+        let checkpoints = self
+            .client
+            .get_checkpoints(&self.target, self.group.name(), Some(shard))
+            .await?;
+        match checkpoints
+            .into_iter()
+            .find_map(|checkpoint| checkpoint.cursor)
+        {
+            Some(cursor) => Ok(cursor),
+            None => {
+                self.client
+                    .get_cursor(&self.target, shard, self.position)
+                    .await
+            }
+        }
+    }
+}
+
+impl<F, Fut> LogGroupProcessor for F
+where
+    F: FnMut(u32, Vec<LogGroup>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    fn process(&mut self, shard: u32, log_groups: Vec<LogGroup>) -> ProcessFuture<'_> {
+        Box::pin(self(shard, log_groups))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ConsumerGroup,
+        proto::*,
+        test_utils::{FakeTransport, test_client},
+        transport::{HttpRequest, HttpResponse},
+    };
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use tokio::sync::Notify;
+
+    const CHECKPOINT: &str =
+        "/consumergroups/group?consumer=worker&forceSuccess=true&type=checkpoint";
+
+    /// Answer the requests of a worker holding shard `0`, with one log group between `c0` and `c1`.
+    fn respond(request: &HttpRequest) -> HttpResponse {
+        let path = request
+            .url
+            .strip_prefix("https://playground.cn-guangzhou.log.aliyuncs.com/logstores/test")
+            .unwrap();
+        match path {
+            "/consumergroups" => HttpResponse::new(400)
+                .with_body(r#"{"errorCode":"ConsumerGroupAlreadyExist","errorMessage":"exists"}"#),
+            "/consumergroups/group" | CHECKPOINT => HttpResponse::new(200),
+            "/consumergroups/group?consumer=worker&type=heartbeat" => {
+                HttpResponse::new(200).with_body("[0]")
+            }
+            "/consumergroups/group?shard=0" => {
+                HttpResponse::new(200).with_body(r#"[{"shard":0,"checkpoint":"","updateTime":0}]"#)
+            }
+            "/shards/0?from=begin&type=cursor" => {
+                HttpResponse::new(200).with_body(r#"{"cursor":"c0"}"#)
+            }
+            "/shards/0?count=1000&cursor=c0&type=log" => {
+                let logs = vec![
+                    Log::new(1700000000, None).with(MayStaticKey::from_static("message"), "hello"),
+                ];
+                let mut group = Vec::new();
+                encode_log_group(&mut group, &LogGroupMetadata::default(), &logs).unwrap();
+                let list = [&[0x0a, group.len() as u8], group.as_slice()].concat();
+                HttpResponse::new(200)
+                    .with_header("x-log-cursor", "c1")
+                    .with_body(list)
+            }
+            "/shards/0?count=1000&cursor=c1&type=log" => {
+                HttpResponse::new(200).with_header("x-log-cursor", "c1")
+            }
+            _ => HttpResponse::new(404),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consumer_worker() {
+        let transport = FakeTransport::new(respond);
+        let client = test_client().transport(transport.clone()).build().unwrap();
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let mut tx = Some(tx);
+        let processor = move |shard: u32, log_groups: Vec<LogGroup>| {
+            assert_eq!(shard, 0);
+            assert_eq!(log_groups[0].logs()[0].get("message"), Some("hello"));
+            tx.take().unwrap().send(()).unwrap();
+            // Slower than the heartbeat interval
+            tokio::time::sleep(Duration::from_millis(100))
+        };
+        ConsumerWorker::new(
+            client,
+            Target::new("playground", "test"),
+            ConsumerGroup::new("group"),
+            "worker",
+            processor,
+            tokio::time::sleep,
+        )
+        .with_heartbeat_interval(Duration::from_millis(10))
+        .with_fetch_interval(Duration::from_millis(10))
+        .with_fetch_count(5000)
+        .with_graceful_shutdown(async {
+            rx.await.ok();
+        })
+        .run()
+        .await
+        .unwrap();
+
+        let requests = transport.requests();
+        let processed = requests
+            .iter()
+            .position(|request| request.url.ends_with("cursor=c0&type=log"))
+            .unwrap();
+        let committed = requests
+            .iter()
+            .position(|request| request.url.ends_with(CHECKPOINT))
+            .unwrap();
+        let heartbeats = requests[processed..committed]
+            .iter()
+            .filter(|request| request.url.ends_with("type=heartbeat"))
+            .count();
+        assert!(heartbeats >= 2, "{heartbeats} heartbeats while processing");
+
+        let checkpoints = requests
+            .into_iter()
+            .filter(|request| request.url.ends_with(CHECKPOINT))
+            .map(|request| String::from_utf8(request.body).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(checkpoints, [r#"{"checkpoint":"c1","shard":0}"#]);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_worker_retry_checkpoint() {
+        let failed = AtomicBool::new(false);
+        let pulled = Arc::new(Notify::new());
+        let transport = FakeTransport::new({
+            let pulled = pulled.clone();
+            move |request| {
+                if request.url.ends_with(CHECKPOINT) && !failed.swap(true, Ordering::Relaxed) {
+                    return HttpResponse::new(500);
+                }
+                if request.url.ends_with("cursor=c1&type=log") {
+                    pulled.notify_one();
+                }
+                respond(request)
+            }
+        });
+        let client = test_client().transport(transport.clone()).build().unwrap();
+
+        let processed = Arc::new(AtomicU32::new(0));
+        let processor = {
+            let processed = processed.clone();
+            move |_: u32, _: Vec<LogGroup>| {
+                processed.fetch_add(1, Ordering::Relaxed);
+                future::ready(())
+            }
+        };
+        ConsumerWorker::new(
+            client,
+            Target::new("playground", "test"),
+            ConsumerGroup::new("group"),
+            "worker",
+            processor,
+            tokio::time::sleep,
+        )
+        .with_fetch_interval(Duration::from_millis(10))
+        .with_graceful_shutdown(async move { pulled.notified().await })
+        .run()
+        .await
+        .unwrap();
+
+        // The failed checkpoint is committed again instead of pulling the logs again
+        assert_eq!(processed.load(Ordering::Relaxed), 1);
+        let pulls = transport
+            .requests()
+            .into_iter()
+            .filter(|request| request.url.ends_with(CHECKPOINT) || request.url.contains("cursor="))
+            .map(|request| request.url.rsplit_once('/').unwrap().1.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pulls,
+            [
+                "0?count=1000&cursor=c0&type=log",
+                "group?consumer=worker&forceSuccess=true&type=checkpoint",
+                "group?consumer=worker&forceSuccess=true&type=checkpoint",
+                "0?count=1000&cursor=c1&type=log",
+            ]
+        );
+    }
+}
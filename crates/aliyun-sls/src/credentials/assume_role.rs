@@ -0,0 +1,170 @@
+use crate::{
+    client::signer,
+    credentials::{Credentials, CredentialsError, CredentialsFuture, CredentialsProvider, sts},
+    transport::HttpTransport,
+};
+use jiff::Timestamp;
+use std::{sync::Arc, time::Duration};
+
+/// Load temporary credentials by assuming a RAM role through STS `AssumeRole`.
+///
+/// The STS request is signed with the credentials of the source provider,
+/// which may itself provide temporary credentials.
+#[derive(Clone)]
+pub struct AssumeRoleProvider {
+    source: Arc<dyn CredentialsProvider>,
+    role_arn: String,
+    role_session_name: Option<String>,
+    duration_seconds: u32,
+    policy: Option<String>,
+    external_id: Option<String>,
+    sts_endpoint: String,
+    timeout: Duration,
+}
+
+impl AssumeRoleProvider {
+    /// Create a provider assuming `role_arn` with the credentials of `source`.
+    pub fn new(source: impl CredentialsProvider, role_arn: impl Into<String>) -> Self {
+        Self {
+            source: Arc::new(source),
+            role_arn: role_arn.into(),
+            role_session_name: None,
+            duration_seconds: 3600,
+            policy: None,
+            external_id: None,
+            sts_endpoint: sts::DEFAULT_STS_ENDPOINT.to_string(),
+            timeout: sts::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set the role session name.
+    ///
+    /// Defaults to a name derived from the current time.
+    pub fn with_role_session_name(mut self, role_session_name: impl Into<String>) -> Self {
+        self.role_session_name = Some(role_session_name.into());
+        self
+    }
+
+    /// Set how long the temporary credentials are valid, in seconds.
+    ///
+    /// Default is `3600`.
+    pub fn with_duration_seconds(mut self, duration_seconds: u32) -> Self {
+        self.duration_seconds = duration_seconds;
+        self
+    }
+
+    /// Set a policy further restricting the permissions of the temporary credentials.
+    pub fn with_policy(mut self, policy: impl Into<String>) -> Self {
+        self.policy = Some(policy.into());
+        self
+    }
+
+    /// Set the external id required by the trust policy of the role.
+    pub fn with_external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Set the STS endpoint.
+    ///
+    /// Default is `https://sts.aliyuncs.com`.
+    pub fn with_sts_endpoint(mut self, sts_endpoint: impl Into<String>) -> Self {
+        self.sts_endpoint = sts_endpoint.into();
+        self
+    }
+
+    /// Set the timeout of the request to STS.
+    ///
+    /// Default is `10s`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn load(&self, transport: &dyn HttpTransport) -> Result<Credentials, CredentialsError> {
+        let source = self.source.provide_credentials(transport).await?;
+        let role_session_name = self
+            .role_session_name
+            .clone()
+            .unwrap_or_else(|| format!("aliyun-sls-{}", Timestamp::now().as_second()));
+
+        let mut params = sts::common_params("AssumeRole");
+        params.insert("AccessKeyId", source.access_key_id().to_string());
+        params.insert("SignatureMethod", "HMAC-SHA1".to_string());
+        params.insert("SignatureVersion", "1.0".to_string());
+        params.insert("SignatureNonce", format!("{:032x}", fastrand::u128(..)));
+        if let Some(security_token) = source.security_token() {
+            params.insert("SecurityToken", security_token.to_string());
+        }
+        params.insert("RoleArn", self.role_arn.clone());
+        params.insert("RoleSessionName", role_session_name);
+        params.insert("DurationSeconds", self.duration_seconds.to_string());
+        if let Some(policy) = &self.policy {
+            params.insert("Policy", policy.clone());
+        }
+        if let Some(external_id) = &self.external_id {
+            params.insert("ExternalId", external_id.clone());
+        }
+
+        let signature = signer::sign_rpc("GET", &params, source.access_key_secret());
+        let query = format!(
+            "{}&Signature={}",
+            signer::canonicalized_query(&params),
+            signer::percent_encode(&signature)
+        );
+        sts::request(transport, &self.sts_endpoint, &query, self.timeout).await
+    }
+}
+
+impl CredentialsProvider for AssumeRoleProvider {
+    fn provide_credentials<'a>(
+        &'a self,
+        transport: &'a dyn HttpTransport,
+    ) -> CredentialsFuture<'a> {
+        Box::pin(self.load(transport))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{serve, transport};
+
+    #[tokio::test]
+    async fn test_assume_role() {
+        let sts_endpoint = serve(|target| {
+            assert!(target.contains("Action=AssumeRole&"));
+            assert!(target.contains("AccessKeyId=id&"));
+            assert!(target.contains("RoleSessionName=session&"));
+            assert!(target.contains("&Signature="));
+            (
+                200,
+                r#"{
+                    "RequestId": "6894B13B-6D71-4EF5-88FA-F32781734A7F",
+                    "AssumedRoleUser": {
+                        "AssumedRoleId": "344584339364951186:session",
+                        "Arn": "acs:ram::123:role/test/session"
+                    },
+                    "Credentials": {
+                        "SecurityToken": "token",
+                        "Expiration": "2030-01-01T00:00:00Z",
+                        "AccessKeySecret": "secret",
+                        "AccessKeyId": "STS.id"
+                    }
+                }"#
+                .to_string(),
+            )
+        })
+        .await;
+
+        let credentials =
+            AssumeRoleProvider::new(Credentials::new("id", "secret"), "acs:ram::123:role/test")
+                .with_role_session_name("session")
+                .with_sts_endpoint(sts_endpoint)
+                .provide_credentials(&*transport())
+                .await
+                .unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.id");
+        assert_eq!(credentials.security_token(), Some("token"));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    credentials::{
+        Credentials, CredentialsError, CredentialsFuture, CredentialsProvider, EcsRamRoleProvider,
+        EnvironmentProvider, OidcRoleProvider, ProfileProvider,
+    },
+    transport::HttpTransport,
+};
+use std::sync::Arc;
+
+/// A chain of credentials providers, tried in order until one provides credentials.
+///
+/// The default chain mirrors the official SDKs:
+/// 1. [`EnvironmentProvider`]
+/// 2. [`OidcRoleProvider`]
+/// 3. [`ProfileProvider`]
+/// 4. [`EcsRamRoleProvider`]
+///
+/// Refreshing reads the OIDC token file and the profile config file with blocking I/O.
+#[derive(Clone)]
+pub struct DefaultCredentialsChain {
+    providers: Vec<Arc<dyn CredentialsProvider>>,
+}
+
+impl Default for DefaultCredentialsChain {
+    fn default() -> Self {
+        Self::empty()
+            .with_provider(EnvironmentProvider::new())
+            .with_provider(OidcRoleProvider::new())
+            .with_provider(ProfileProvider::new())
+            .with_provider(EcsRamRoleProvider::new())
+    }
+}
+
+impl DefaultCredentialsChain {
+    /// Create the default credentials chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a chain without any provider.
+    pub fn empty() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Append a provider to the chain.
+    pub fn with_provider(mut self, provider: impl CredentialsProvider) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    async fn load(&self, transport: &dyn HttpTransport) -> Result<Credentials, CredentialsError> {
+        let mut errors = Vec::with_capacity(self.providers.len());
+        for provider in &self.providers {
+            match provider.provide_credentials(transport).await {
+                Ok(credentials) => return Ok(credentials),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        Err(CredentialsError::NotFound(
+            format!(
+                "no provider in the chain succeeded: [{}]",
+                errors.join(", ")
+            )
+            .into(),
+        ))
+    }
+}
+
+impl CredentialsProvider for DefaultCredentialsChain {
+    fn provide_credentials<'a>(
+        &'a self,
+        transport: &'a dyn HttpTransport,
+    ) -> CredentialsFuture<'a> {
+        Box::pin(self.load(transport))
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    credentials::{
+        Credentials, CredentialsError, CredentialsFuture, CredentialsProvider, env::non_empty_var,
+    },
+    transport::{HttpRequest, HttpTransport, Method},
+};
+use jiff::Timestamp;
+use std::time::Duration;
+
+const DEFAULT_BASE_URL: &str = "http://100.100.100.200";
+const SECURITY_CREDENTIALS_PATH: &str = "/latest/meta-data/ram/security-credentials/";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+const ECS_METADATA: &str = "ALIBABA_CLOUD_ECS_METADATA";
+const ECS_METADATA_DISABLED: &str = "ALIBABA_CLOUD_ECS_METADATA_DISABLED";
+
+/// Load temporary credentials of the RAM role attached to the ECS instance.
+///
+/// The role name is read from `ALIBABA_CLOUD_ECS_METADATA` if not set explicitly,
+/// or discovered from the instance metadata service otherwise.
+/// Setting `ALIBABA_CLOUD_ECS_METADATA_DISABLED=true` disables the provider.
+#[derive(Debug, Clone)]
+pub struct EcsRamRoleProvider {
+    base_url: String,
+    role_name: Option<String>,
+    timeout: Duration,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SecurityCredentials {
+    code: String,
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+impl Default for EcsRamRoleProvider {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            role_name: non_empty_var(ECS_METADATA),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl EcsRamRoleProvider {
+    /// Create a new ECS RAM role credentials provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the base URL of the instance metadata service.
+    ///
+    /// Default is `http://100.100.100.200`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Set the RAM role name instead of discovering it.
+    pub fn with_role_name(mut self, role_name: impl Into<String>) -> Self {
+        self.role_name = Some(role_name.into());
+        self
+    }
+
+    /// Set the timeout of each request to the instance metadata service.
+    ///
+    /// Default is `1s`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn load(&self, transport: &dyn HttpTransport) -> Result<Credentials, CredentialsError> {
+        if non_empty_var(ECS_METADATA_DISABLED).is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+            return Err(CredentialsError::NotFound(
+                "ecs metadata is disabled".into(),
+            ));
+        }
+
+        let base_url = self.base_url.trim_end_matches('/');
+        let role_name = match &self.role_name {
+            Some(role_name) => role_name.clone(),
+            None => {
+                let url = format!("{base_url}{SECURITY_CREDENTIALS_PATH}");
+                let role_name = self.get(transport, &url).await?;
+                let role_name = role_name.trim();
+                if role_name.is_empty() {
+                    return Err(CredentialsError::NotFound(
+                        "no ram role attached to the ecs instance".into(),
+                    ));
+                }
+                role_name.to_string()
+            }
+        };
+
+        let url = format!("{base_url}{SECURITY_CREDENTIALS_PATH}{role_name}");
+        let body = self.get(transport, &url).await?;
+        let credentials: SecurityCredentials =
+            serde_json::from_str(&body).map_err(CredentialsError::provider)?;
+        if credentials.code != "Success" {
+            return Err(CredentialsError::provider(format!(
+                "failed to get credentials of ram role {role_name}: {}",
+                credentials.code
+            )));
+        }
+
+        let expiration: Timestamp = credentials
+            .expiration
+            .parse()
+            .map_err(CredentialsError::provider)?;
+        Ok(
+            Credentials::new(credentials.access_key_id, credentials.access_key_secret)
+                .with_security_token(credentials.security_token)
+                .with_expiration(expiration),
+        )
+    }
+
+    async fn get(
+        &self,
+        transport: &dyn HttpTransport,
+        url: &str,
+    ) -> Result<String, CredentialsError> {
+        let request = HttpRequest {
+            method: Method::Get,
+            url: url.to_string(),
+            headers: vec![],
+            body: vec![],
+            timeout: Some(self.timeout),
+            read_body: true,
+        };
+        let res = transport
+            .send(request)
+            .await
+            .map_err(CredentialsError::provider)?;
+        let body = res.text();
+        if !res.is_success() {
+            return Err(CredentialsError::provider(format!(
+                "ecs metadata request failed [{}] {body}",
+                res.status
+            )));
+        }
+        Ok(body)
+    }
+}
+
+impl CredentialsProvider for EcsRamRoleProvider {
+    fn provide_credentials<'a>(
+        &'a self,
+        transport: &'a dyn HttpTransport,
+    ) -> CredentialsFuture<'a> {
+        Box::pin(self.load(transport))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{serve, transport};
+
+    #[tokio::test]
+    async fn test_ecs_ram_role() {
+        let base_url = serve(|target| match target {
+            "/latest/meta-data/ram/security-credentials/" => (200, "test-role".to_string()),
+            "/latest/meta-data/ram/security-credentials/test-role" => (
+                200,
+                r#"{
+                    "AccessKeyId": "STS.id",
+                    "AccessKeySecret": "secret",
+                    "Expiration": "2030-01-01T00:00:00Z",
+                    "SecurityToken": "token",
+                    "LastUpdated": "2029-12-31T18:00:00Z",
+                    "Code": "Success"
+                }"#
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        })
+        .await;
+
+        let credentials = EcsRamRoleProvider::new()
+            .with_base_url(base_url)
+            .provide_credentials(&*transport())
+            .await
+            .unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.id");
+        assert_eq!(credentials.access_key_secret(), "secret");
+        assert_eq!(credentials.security_token(), Some("token"));
+        assert_eq!(
+            credentials.expiration(),
+            Some("2030-01-01T00:00:00Z".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    credentials::{Credentials, CredentialsError, CredentialsFuture, CredentialsProvider},
+    transport::HttpTransport,
+};
+use std::env;
+
+const ACCESS_KEY_ID: &str = "ALIBABA_CLOUD_ACCESS_KEY_ID";
+const ACCESS_KEY_SECRET: &str = "ALIBABA_CLOUD_ACCESS_KEY_SECRET";
+const SECURITY_TOKEN: &str = "ALIBABA_CLOUD_SECURITY_TOKEN";
+
+/// Load credentials from environment variables.
+///
+/// Reads `ALIBABA_CLOUD_ACCESS_KEY_ID`, `ALIBABA_CLOUD_ACCESS_KEY_SECRET`
+/// and the optional `ALIBABA_CLOUD_SECURITY_TOKEN`.
+#[derive(Debug, Clone, Default)]
+pub struct EnvironmentProvider {
+    _priv: (),
+}
+
+impl EnvironmentProvider {
+    /// Create a new environment credentials provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn load(&self) -> Result<Credentials, CredentialsError> {
+        let access_key_id =
+            non_empty_var(ACCESS_KEY_ID).ok_or(CredentialsError::NotFound(ACCESS_KEY_ID.into()))?;
+        let access_key_secret = non_empty_var(ACCESS_KEY_SECRET)
+            .ok_or(CredentialsError::NotFound(ACCESS_KEY_SECRET.into()))?;
+
+        let credentials = Credentials::new(access_key_id, access_key_secret);
+        Ok(match non_empty_var(SECURITY_TOKEN) {
+            Some(security_token) => credentials.with_security_token(security_token),
+            None => credentials,
+        })
+    }
+}
+
+impl CredentialsProvider for EnvironmentProvider {
+    fn provide_credentials<'a>(&'a self, _: &'a dyn HttpTransport) -> CredentialsFuture<'a> {
+        Box::pin(async move { self.load() })
+    }
+}
+
+pub(super) fn non_empty_var(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.is_empty())
+}
@@ -0,0 +1,236 @@
+//! Credentials used to sign requests to the SLS service.
+pub use self::{
+    assume_role::AssumeRoleProvider, chain::DefaultCredentialsChain, ecs::EcsRamRoleProvider,
+    env::EnvironmentProvider, oidc::OidcRoleProvider, profile::ProfileProvider,
+};
+use crate::transport::HttpTransport;
+use async_lock::{Mutex, RwLock};
+use jiff::{SignedDuration, Timestamp};
+use std::{borrow::Cow, error::Error, fmt, pin::Pin, sync::Arc};
+
+mod assume_role;
+mod chain;
+mod ecs;
+mod env;
+mod oidc;
+mod profile;
+mod sts;
+
+/// Credentials are refreshed this long before they expire.
+const REFRESH_AHEAD: SignedDuration = SignedDuration::from_mins(5);
+
+/// Future returned by [`CredentialsProvider::provide_credentials`].
+pub type CredentialsFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Credentials, CredentialsError>> + Send + 'a>>;
+
+/// Trait for providing credentials to the SLS client.
+///
+/// The client caches the returned credentials, and asks for new ones before they expire.
+pub trait CredentialsProvider: Send + Sync + 'static {
+    /// Load the credentials.
+    ///
+    /// HTTP requests, if any, should be sent with `transport`, the transport of the client.
+    fn provide_credentials<'a>(&'a self, transport: &'a dyn HttpTransport)
+    -> CredentialsFuture<'a>;
+}
+
+/// Access key credentials, optionally temporary.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: Option<String>,
+    expiration: Option<Timestamp>,
+}
+
+/// Error type for loading credentials.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CredentialsError {
+    /// The provider has no credentials to provide.
+    #[error("credentials not found: {0}")]
+    NotFound(Cow<'static, str>),
+    /// The provider failed to load credentials.
+    #[error("failed to load credentials: {0}")]
+    Provider(#[source] Box<dyn Error + Send + Sync>),
+}
+
+/// Caches the credentials of a provider, refreshing them ahead of expiration.
+pub(crate) struct CredentialsCache {
+    provider: Arc<dyn CredentialsProvider>,
+    transport: Arc<dyn HttpTransport>,
+    cached: RwLock<Option<Arc<Credentials>>>,
+    /// Held while refreshing, so that only one request asks the provider.
+    refresh: Mutex<()>,
+}
+
+impl Credentials {
+    /// Create new long-term credentials.
+    pub fn new(access_key_id: impl Into<String>, access_key_secret: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            access_key_secret: access_key_secret.into(),
+            security_token: None,
+            expiration: None,
+        }
+    }
+
+    /// Set the STS security token of temporary credentials.
+    pub fn with_security_token(mut self, security_token: impl Into<String>) -> Self {
+        self.security_token = Some(security_token.into());
+        self
+    }
+
+    /// Set the time the credentials expire.
+    pub fn with_expiration(mut self, expiration: Timestamp) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    /// Access key id.
+    pub fn access_key_id(&self) -> &str {
+        &self.access_key_id
+    }
+
+    /// Access key secret.
+    pub fn access_key_secret(&self) -> &str {
+        &self.access_key_secret
+    }
+
+    /// STS security token, if the credentials are temporary.
+    pub fn security_token(&self) -> Option<&str> {
+        self.security_token.as_deref()
+    }
+
+    /// Time the credentials expire, `None` if they never expire.
+    pub fn expiration(&self) -> Option<Timestamp> {
+        self.expiration
+    }
+
+    fn needs_refresh(&self) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration.duration_since(Timestamp::now()) < REFRESH_AHEAD)
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration <= Timestamp::now())
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("access_key_secret", &"** redacted **")
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "** redacted **"),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+impl CredentialsProvider for Credentials {
+    fn provide_credentials<'a>(&'a self, _: &'a dyn HttpTransport) -> CredentialsFuture<'a> {
+        Box::pin(async move { Ok(self.clone()) })
+    }
+}
+
+impl CredentialsError {
+    /// Create a [`CredentialsError::Provider`] from any error.
+    pub fn provider(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        CredentialsError::Provider(err.into())
+    }
+}
+
+impl CredentialsCache {
+    pub(crate) fn new(
+        provider: Arc<dyn CredentialsProvider>,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Self {
+        Self {
+            provider,
+            transport,
+            cached: RwLock::new(None),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// Get the cached credentials, refreshing them if they are about to expire.
+    ///
+    /// Credentials which are still valid are returned while another request is refreshing
+    /// them, or if refreshing them fails, only expired credentials wait for the provider.
+    pub(crate) async fn get(&self) -> Result<Arc<Credentials>, CredentialsError> {
+        let current = self.cached.read().await.clone();
+        if let Some(credentials) = &current {
+            if !credentials.needs_refresh() {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let _refresh = match (self.refresh.try_lock(), current) {
+            (Some(refresh), _) => refresh,
+            (None, Some(credentials)) if !credentials.is_expired() => return Ok(credentials),
+            (None, _) => self.refresh.lock().await,
+        };
+        // Someone else may have refreshed while we were waiting for the lock
+        let current = self.cached.read().await.clone();
+        if let Some(credentials) = &current {
+            if !credentials.needs_refresh() {
+                return Ok(credentials.clone());
+            }
+        }
+        match self.provider.provide_credentials(&*self.transport).await {
+            Ok(credentials) => {
+                let credentials = Arc::new(credentials);
+                *self.cached.write().await = Some(credentials.clone());
+                Ok(credentials)
+            }
+            Err(e) => match current {
+                Some(credentials) if !credentials.is_expired() => Ok(credentials),
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::transport;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_credentials_cache() {
+        /// Provides credentials expiring in a minute once, then fails.
+        struct FlakyProvider(AtomicBool);
+
+        impl CredentialsProvider for FlakyProvider {
+            fn provide_credentials<'a>(
+                &'a self,
+                _: &'a dyn HttpTransport,
+            ) -> CredentialsFuture<'a> {
+                Box::pin(async move {
+                    if self.0.swap(true, Ordering::Relaxed) {
+                        return Err(CredentialsError::provider("sts is down"));
+                    }
+                    let expiration = Timestamp::now() + SignedDuration::from_mins(1);
+                    Ok(Credentials::new("id", "secret").with_expiration(expiration))
+                })
+            }
+        }
+
+        let cache =
+            CredentialsCache::new(Arc::new(FlakyProvider(AtomicBool::new(false))), transport());
+        let credentials = cache.get().await.unwrap();
+        assert!(credentials.needs_refresh());
+        // The refresh fails, but the credentials are still valid for a minute
+        assert_eq!(cache.get().await.unwrap(), credentials);
+
+        let cache =
+            CredentialsCache::new(Arc::new(FlakyProvider(AtomicBool::new(true))), transport());
+        assert!(cache.get().await.is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    client::signer,
+    credentials::{
+        Credentials, CredentialsError, CredentialsFuture, CredentialsProvider, env::non_empty_var,
+        sts,
+    },
+    transport::HttpTransport,
+};
+use jiff::Timestamp;
+use std::{path::PathBuf, time::Duration};
+
+const ROLE_ARN: &str = "ALIBABA_CLOUD_ROLE_ARN";
+const OIDC_PROVIDER_ARN: &str = "ALIBABA_CLOUD_OIDC_PROVIDER_ARN";
+const OIDC_TOKEN_FILE: &str = "ALIBABA_CLOUD_OIDC_TOKEN_FILE";
+const ROLE_SESSION_NAME: &str = "ALIBABA_CLOUD_ROLE_SESSION_NAME";
+
+/// Load temporary credentials by exchanging an OIDC token through STS `AssumeRoleWithOIDC`.
+///
+/// This is how pods authenticate on ACK clusters with RRSA enabled. Unless set explicitly,
+/// the role ARN, OIDC provider ARN, token file and session name are read from
+/// `ALIBABA_CLOUD_ROLE_ARN`, `ALIBABA_CLOUD_OIDC_PROVIDER_ARN`,
+/// `ALIBABA_CLOUD_OIDC_TOKEN_FILE` and `ALIBABA_CLOUD_ROLE_SESSION_NAME`.
+///
+/// The token file is read again on every refresh, so a rotated token is picked up.
+#[derive(Debug, Clone)]
+pub struct OidcRoleProvider {
+    role_arn: Option<String>,
+    oidc_provider_arn: Option<String>,
+    oidc_token_file: Option<PathBuf>,
+    role_session_name: Option<String>,
+    duration_seconds: u32,
+    policy: Option<String>,
+    sts_endpoint: String,
+    timeout: Duration,
+}
+
+impl Default for OidcRoleProvider {
+    fn default() -> Self {
+        Self {
+            role_arn: non_empty_var(ROLE_ARN),
+            oidc_provider_arn: non_empty_var(OIDC_PROVIDER_ARN),
+            oidc_token_file: non_empty_var(OIDC_TOKEN_FILE).map(PathBuf::from),
+            role_session_name: non_empty_var(ROLE_SESSION_NAME),
+            duration_seconds: 3600,
+            policy: None,
+            sts_endpoint: sts::DEFAULT_STS_ENDPOINT.to_string(),
+            timeout: sts::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl OidcRoleProvider {
+    /// Create a new OIDC role credentials provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the ARN of the RAM role to assume.
+    pub fn with_role_arn(mut self, role_arn: impl Into<String>) -> Self {
+        self.role_arn = Some(role_arn.into());
+        self
+    }
+
+    /// Set the ARN of the OIDC identity provider.
+    pub fn with_oidc_provider_arn(mut self, oidc_provider_arn: impl Into<String>) -> Self {
+        self.oidc_provider_arn = Some(oidc_provider_arn.into());
+        self
+    }
+
+    /// Set the path of the OIDC token file.
+    pub fn with_oidc_token_file(mut self, oidc_token_file: impl Into<PathBuf>) -> Self {
+        self.oidc_token_file = Some(oidc_token_file.into());
+        self
+    }
+
+    /// Set the role session name.
+    ///
+    /// Defaults to a name derived from the current time.
+    pub fn with_role_session_name(mut self, role_session_name: impl Into<String>) -> Self {
+        self.role_session_name = Some(role_session_name.into());
+        self
+    }
+
+    /// Set how long the temporary credentials are valid, in seconds.
+    ///
+    /// Default is `3600`.
+    pub fn with_duration_seconds(mut self, duration_seconds: u32) -> Self {
+        self.duration_seconds = duration_seconds;
+        self
+    }
+
+    /// Set a policy further restricting the permissions of the temporary credentials.
+    pub fn with_policy(mut self, policy: impl Into<String>) -> Self {
+        self.policy = Some(policy.into());
+        self
+    }
+
+    /// Set the STS endpoint.
+    ///
+    /// Default is `https://sts.aliyuncs.com`.
+    pub fn with_sts_endpoint(mut self, sts_endpoint: impl Into<String>) -> Self {
+        self.sts_endpoint = sts_endpoint.into();
+        self
+    }
+
+    /// Set the timeout of the request to STS.
+    ///
+    /// Default is `10s`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn load(&self, transport: &dyn HttpTransport) -> Result<Credentials, CredentialsError> {
+        let role_arn = self
+            .role_arn
+            .clone()
+            .ok_or(CredentialsError::NotFound(ROLE_ARN.into()))?;
+        let oidc_provider_arn = self
+            .oidc_provider_arn
+            .clone()
+            .ok_or(CredentialsError::NotFound(OIDC_PROVIDER_ARN.into()))?;
+        let oidc_token_file = self
+            .oidc_token_file
+            .as_ref()
+            .ok_or(CredentialsError::NotFound(OIDC_TOKEN_FILE.into()))?;
+        let oidc_token =
+            std::fs::read_to_string(oidc_token_file).map_err(CredentialsError::provider)?;
+        let role_session_name = self
+            .role_session_name
+            .clone()
+            .unwrap_or_else(|| format!("aliyun-sls-{}", Timestamp::now().as_second()));
+
+        let mut params = sts::common_params("AssumeRoleWithOIDC");
+        params.insert("RoleArn", role_arn);
+        params.insert("OIDCProviderArn", oidc_provider_arn);
+        params.insert("OIDCToken", oidc_token.trim().to_string());
+        params.insert("RoleSessionName", role_session_name);
+        params.insert("DurationSeconds", self.duration_seconds.to_string());
+        if let Some(policy) = &self.policy {
+            params.insert("Policy", policy.clone());
+        }
+
+        let query = signer::canonicalized_query(&params);
+        sts::request(transport, &self.sts_endpoint, &query, self.timeout).await
+    }
+}
+
+impl CredentialsProvider for OidcRoleProvider {
+    fn provide_credentials<'a>(
+        &'a self,
+        transport: &'a dyn HttpTransport,
+    ) -> CredentialsFuture<'a> {
+        Box::pin(self.load(transport))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{serve, transport};
+
+    #[tokio::test]
+    async fn test_assume_role_with_oidc() {
+        let sts_endpoint = serve(|target| {
+            assert!(target.contains("Action=AssumeRoleWithOIDC"));
+            assert!(target.contains("OIDCToken=oidc-token"));
+            assert!(target.contains("RoleArn=acs%3Aram%3A%3A123%3Arole%2Ftest"));
+            (
+                200,
+                r#"{
+                    "RequestId": "3D57EAD2-8723-1F26-B69C-F8707D8B565D",
+                    "Credentials": {
+                        "SecurityToken": "token",
+                        "Expiration": "2030-01-01T00:00:00Z",
+                        "AccessKeySecret": "secret",
+                        "AccessKeyId": "STS.id"
+                    }
+                }"#
+                .to_string(),
+            )
+        })
+        .await;
+
+        let token_file =
+            std::env::temp_dir().join(format!("aliyun-sls-test-oidc-token-{}", std::process::id()));
+        std::fs::write(&token_file, "oidc-token\n").unwrap();
+
+        let credentials = OidcRoleProvider::new()
+            .with_role_arn("acs:ram::123:role/test")
+            .with_oidc_provider_arn("acs:ram::123:oidc-provider/ack-rrsa")
+            .with_oidc_token_file(&token_file)
+            .with_sts_endpoint(sts_endpoint)
+            .provide_credentials(&*transport())
+            .await
+            .unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.id");
+        assert_eq!(credentials.security_token(), Some("token"));
+
+        std::fs::remove_file(&token_file).unwrap();
+    }
+}
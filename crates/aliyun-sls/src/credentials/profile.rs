@@ -0,0 +1,202 @@
+use crate::{
+    credentials::{
+        AssumeRoleProvider, Credentials, CredentialsError, CredentialsFuture, CredentialsProvider,
+        EcsRamRoleProvider, env::non_empty_var,
+    },
+    transport::HttpTransport,
+};
+use std::{io, path::PathBuf};
+
+const PROFILE: &str = "ALIBABA_CLOUD_PROFILE";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Load credentials from a profile of the Aliyun CLI config file.
+///
+/// The config file defaults to `~/.aliyun/config.json`. The profile is, in order,
+/// the one set explicitly, the one named by `ALIBABA_CLOUD_PROFILE`,
+/// the `current` profile of the config file, or `default`.
+///
+/// Supported profile modes are `AK`, `StsToken`, `RamRoleArn` and `EcsRamRole`.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileProvider {
+    path: Option<PathBuf>,
+    profile: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Config {
+    #[serde(default)]
+    current: String,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+}
+
+#[derive(serde::Deserialize)]
+struct Profile {
+    name: String,
+    #[serde(default)]
+    mode: String,
+    #[serde(default)]
+    access_key_id: String,
+    #[serde(default)]
+    access_key_secret: String,
+    #[serde(default)]
+    sts_token: String,
+    #[serde(default)]
+    ram_role_name: String,
+    #[serde(default)]
+    ram_role_arn: String,
+    #[serde(default)]
+    ram_session_name: String,
+    #[serde(default)]
+    expired_seconds: u32,
+}
+
+impl ProfileProvider {
+    /// Create a new profile credentials provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the path of the config file.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the profile name.
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    async fn load(&self, transport: &dyn HttpTransport) -> Result<Credentials, CredentialsError> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => {
+                default_config_path().ok_or(CredentialsError::NotFound("home directory".into()))?
+            }
+        };
+        let config = match std::fs::read(&path) {
+            Ok(config) => config,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(CredentialsError::NotFound(
+                    format!("config file {}", path.display()).into(),
+                ));
+            }
+            Err(e) => return Err(CredentialsError::provider(e)),
+        };
+        let config: Config = serde_json::from_slice(&config).map_err(CredentialsError::provider)?;
+
+        let name = self
+            .profile
+            .clone()
+            .or_else(|| non_empty_var(PROFILE))
+            .or_else(|| Some(config.current).filter(|current| !current.is_empty()))
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let profile = config
+            .profiles
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| CredentialsError::NotFound(format!("profile {name}").into()))?;
+
+        match profile.mode.as_str() {
+            "AK" => Ok(Credentials::new(
+                profile.access_key_id,
+                profile.access_key_secret,
+            )),
+            "StsToken" => Ok(
+                Credentials::new(profile.access_key_id, profile.access_key_secret)
+                    .with_security_token(profile.sts_token),
+            ),
+            "RamRoleArn" => {
+                let source = Credentials::new(profile.access_key_id, profile.access_key_secret);
+                let mut provider = AssumeRoleProvider::new(source, profile.ram_role_arn);
+                if !profile.ram_session_name.is_empty() {
+                    provider = provider.with_role_session_name(profile.ram_session_name);
+                }
+                if profile.expired_seconds > 0 {
+                    provider = provider.with_duration_seconds(profile.expired_seconds);
+                }
+                provider.provide_credentials(transport).await
+            }
+            "EcsRamRole" => {
+                EcsRamRoleProvider::new()
+                    .with_role_name(profile.ram_role_name)
+                    .provide_credentials(transport)
+                    .await
+            }
+            mode => Err(CredentialsError::provider(format!(
+                "unsupported mode {mode} of profile {name}"
+            ))),
+        }
+    }
+}
+
+impl CredentialsProvider for ProfileProvider {
+    fn provide_credentials<'a>(
+        &'a self,
+        transport: &'a dyn HttpTransport,
+    ) -> CredentialsFuture<'a> {
+        Box::pin(self.load(transport))
+    }
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    let home = non_empty_var("HOME").or_else(|| non_empty_var("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".aliyun").join("config.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::transport;
+
+    #[tokio::test]
+    async fn test_profile() {
+        let path = std::env::temp_dir().join(format!(
+            "aliyun-sls-test-config-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"{
+                "current": "sts",
+                "profiles": [
+                    {
+                        "name": "default",
+                        "mode": "AK",
+                        "access_key_id": "id",
+                        "access_key_secret": "secret"
+                    },
+                    {
+                        "name": "sts",
+                        "mode": "StsToken",
+                        "access_key_id": "STS.id",
+                        "access_key_secret": "sts-secret",
+                        "sts_token": "token"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let provider = ProfileProvider::new().with_path(&path);
+        let credentials = provider.provide_credentials(&*transport()).await.unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.id");
+        assert_eq!(credentials.security_token(), Some("token"));
+
+        let provider = provider.with_profile("default");
+        let credentials = provider.provide_credentials(&*transport()).await.unwrap();
+        assert_eq!(credentials.access_key_id(), "id");
+        assert_eq!(credentials.security_token(), None);
+
+        let provider = provider.with_profile("missing");
+        assert!(matches!(
+            provider.provide_credentials(&*transport()).await,
+            Err(CredentialsError::NotFound(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
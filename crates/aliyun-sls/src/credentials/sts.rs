@@ -0,0 +1,85 @@
+use crate::{
+    credentials::{Credentials, CredentialsError},
+    transport::{HttpRequest, HttpTransport, Method},
+};
+use jiff::Timestamp;
+use std::{collections::BTreeMap, time::Duration};
+
+pub(super) const DEFAULT_STS_ENDPOINT: &str = "https://sts.aliyuncs.com";
+pub(super) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const API_VERSION: &str = "2015-04-01";
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsResponse {
+    credentials: Option<StsCredentials>,
+    code: Option<String>,
+    message: Option<String>,
+    request_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+/// Parameters shared by all STS actions.
+pub(super) fn common_params(action: &'static str) -> BTreeMap<&'static str, String> {
+    BTreeMap::from([
+        ("Action", action.to_string()),
+        ("Format", "JSON".to_string()),
+        ("Version", API_VERSION.to_string()),
+        (
+            "Timestamp",
+            Timestamp::now().strftime("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        ),
+    ])
+}
+
+/// Call a STS action, returning the temporary credentials.
+pub(super) async fn request(
+    transport: &dyn HttpTransport,
+    endpoint: &str,
+    query: &str,
+    timeout: Duration,
+) -> Result<Credentials, CredentialsError> {
+    let request = HttpRequest {
+        method: Method::Get,
+        url: format!("{}/?{query}", endpoint.trim_end_matches('/')),
+        headers: vec![],
+        body: vec![],
+        timeout: Some(timeout),
+        read_body: true,
+    };
+    let res = transport
+        .send(request)
+        .await
+        .map_err(CredentialsError::provider)?;
+    let status = res.status;
+    let body = res.text();
+
+    let res: StsResponse = serde_json::from_str(&body)
+        .map_err(|_| CredentialsError::provider(format!("sts request failed [{status}] {body}")))?;
+    let Some(credentials) = res.credentials else {
+        return Err(CredentialsError::provider(format!(
+            "sts request failed [{status}] {}: {} (request id: {})",
+            res.code.unwrap_or_default(),
+            res.message.unwrap_or_default(),
+            res.request_id.unwrap_or_default(),
+        )));
+    };
+
+    let expiration: Timestamp = credentials
+        .expiration
+        .parse()
+        .map_err(CredentialsError::provider)?;
+    Ok(
+        Credentials::new(credentials.access_key_id, credentials.access_key_secret)
+            .with_security_token(credentials.security_token)
+            .with_expiration(expiration),
+    )
+}
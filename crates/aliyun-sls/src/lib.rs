@@ -4,6 +4,9 @@
 //!
 //! ### Http backend
 //!
+//! At least one backend must be enabled. Backends can be enabled together, the one to use is
+//! picked at runtime with `SlsClientBuilder::http_backend`, `reqwest` is used by default.
+//!
 //! - [`reqwest`]:
 //!   `reqwest` feature gate Use [`reqwest`] as the HTTP backend, but do not enable any TLS features.
 //!   - `reqwest-default-tls`: use [`reqwest`] as the HTTP backend and default TLS provider.
@@ -18,12 +21,28 @@
 //!   - [`nyquest-backend-curl`]: libcurl backend for [`nyquest`], requires libcurl _7.68.0_ or later.
 //!   - [`nyquest-backend-nsurlsession`]: macOS/iOS [`NSURLSession`] backend for [`nyquest`].
 //!
+//! ### Blocking client
+//!
+//! - `blocking`: enable `BlockingSlsClient`, sending requests with [`reqwest`]'s blocking client.
+//!
+//! ### Shard routing
+//!
+//! - `derive-key`: enable `HashKey::derive`, deriving the shard key (128 bits hex) from any string using BLAKE3.
+//!
+//! ### Consumer group
+//!
+//! - `consumer`: enable `ConsumerWorker`, consuming the shards assigned to it in a consumer group.
+//!
 //! ### Compression
 //!
-//! > Note: `lz4` and `deflate` cannot be enabled at the same time.
+//! Codecs can be enabled together, the one to use is picked at runtime with
+//! `SlsClientBuilder::compression`. By default the first enabled codec in the order below is used.
 //!
 //! - `lz4`: enable lz4 compression for logs.
 //! - `deflate`: enable deflate compression for logs.
+//! - `zstd`: enable zstd compression for logs.
+//!
+//! Pulling logs requires at least one codec, the first enabled one is requested.
 //!
 //! ### Inline Optimizations
 //!
@@ -64,17 +83,29 @@
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-#[cfg(all(all(feature = "lz4", feature = "deflate"), not(docsrs)))]
-compile_error!("`lz4` and `deflate` cannot be enabled at the same time");
-
 mod client;
+#[cfg(feature = "consumer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "consumer")))]
+pub mod consumer;
+pub mod credentials;
 mod proto;
 #[cfg(feature = "reporter")]
 #[cfg_attr(docsrs, doc(cfg(feature = "reporter")))]
 pub mod reporter;
+#[cfg(test)]
+mod test_utils;
+pub mod transport;
 
-pub use client::{SlsClient, SlsClientBuilder, SlsClientBuilderError, SlsClientError};
-pub use proto::{Log, LogGroupMetadata, MayStaticKey};
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub use client::BlockingSlsClient;
+pub use client::{
+    Checkpoint, Compression, ConsumerGroup, Cursor, CursorPosition, Endpoint, ErrorCode,
+    FieldIndex, FieldType, FullTextIndex, GetLogs, GetLogsResponse, HashKey, Index, Jitter,
+    LogStore, ParseHashKeyError, PullLogsResponse, RetryPolicy, RetryTimer, Shard, ShardStatus,
+    SignatureVersion, SlsClient, SlsClientBuilder, SlsClientBuilderError, SlsClientError, Target,
+};
+pub use proto::{DecodeError, Log, LogGroup, LogGroupMetadata, MayStaticKey};
 
 /// Inline constants
 pub mod inline {
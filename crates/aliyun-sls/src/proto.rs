@@ -1,7 +1,8 @@
+use crate::Compression;
 use compact_str::CompactString;
 use std::hash::Hash;
 use std::sync::Arc;
-use std::{borrow::Borrow, io, io::Write};
+use std::{borrow::Borrow, error::Error, io, io::Write};
 
 cfg_if::cfg_if! {
     if #[cfg(all(feature = "inline-keypairs-16", not(feature = "inline-none")))] {
@@ -61,6 +62,37 @@ pub struct LogGroupMetadata {
     log_tags: Map<MayStaticKey, CompactString, N_INLINE_TAGS>,
 }
 
+/// Log group decoded from its protobuf encoding, e.g. returned by PullLogs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LogGroup {
+    metadata: LogGroupMetadata,
+    logs: Vec<Log>,
+}
+
+/// Error decoding a protobuf encoded log group.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The buffer ends in the middle of a field.
+    #[error("unexpected end of buffer")]
+    UnexpectedEof,
+    /// A varint is longer than 10 bytes.
+    #[error("invalid varint")]
+    InvalidVarint,
+    /// Unknown or unsupported wire type.
+    #[error("unsupported wire type {0}")]
+    InvalidWireType(u8),
+    /// A string field is not valid UTF-8.
+    #[error("invalid utf-8 in field {0}")]
+    InvalidUtf8(&'static str),
+    /// A required field is missing.
+    #[error("missing required field {0}")]
+    MissingField(&'static str),
+    /// Failed to decompress the buffer.
+    #[error("failed to decompress: {0}")]
+    Decompress(#[source] Box<dyn Error + Send + Sync>),
+}
+
 impl MayStaticKey {
     /// Create a new `MayStaticKey` from a string.
     pub fn new(s: impl Into<CompactString>) -> Self {
@@ -179,6 +211,28 @@ impl Log {
         self.contents.remove(key);
         self
     }
+
+    /// Timestamp of the log, in seconds since the UNIX epoch.
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+
+    /// Subsecond nanosecond of the log, if any.
+    pub fn subsec_nanosecond(&self) -> Option<u32> {
+        self.subsec_nanosecond
+    }
+
+    /// Value of the given key in the log contents.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.contents.get(key).map(CompactString::as_str)
+    }
+
+    /// Key-value pairs of the log contents, ordered by key.
+    pub fn contents(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.contents
+            .iter()
+            .map(|(key, value)| (key.as_ref(), value.as_str()))
+    }
 }
 
 impl LogGroupMetadata {
@@ -220,6 +274,89 @@ impl LogGroupMetadata {
         self.log_tags.remove(key);
         self
     }
+
+    /// Topic of the log group, empty if not set.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Source of the log group, empty if not set.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Value of the given tag.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.log_tags.get(key).map(CompactString::as_str)
+    }
+
+    /// Tags of the log group, ordered by key.
+    pub fn tags(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.log_tags
+            .iter()
+            .map(|(key, value)| (key.as_ref(), value.as_str()))
+    }
+}
+
+impl LogGroup {
+    /// Create a log group from its metadata and logs.
+    pub fn new(metadata: LogGroupMetadata, logs: Vec<Log>) -> Self {
+        LogGroup { metadata, logs }
+    }
+
+    /// Decode a protobuf encoded `LogGroup`.
+    ///
+    /// Compressed buffers are decoded with [`LogGroup::decode_compressed`].
+    pub fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        decode_log_group(buf)
+    }
+
+    /// Decode a `LogGroup` compressed with `compression`, `raw_size` bytes long once decompressed.
+    pub fn decode_compressed(
+        buf: &[u8],
+        compression: Compression,
+        raw_size: usize,
+    ) -> Result<Self, DecodeError> {
+        decode_log_group(&compression.decompress(buf, raw_size)?)
+    }
+
+    /// Decode a protobuf encoded `LogGroupList`.
+    pub fn decode_list(buf: &[u8]) -> Result<Vec<Self>, DecodeError> {
+        let mut reader = Reader(buf);
+        let mut groups = Vec::new();
+        while !reader.is_empty() {
+            match reader.key()? {
+                (1, WireType::LengthDelimited) => groups.push(decode_log_group(reader.bytes()?)?),
+                (_, wire_type) => reader.skip(wire_type)?,
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Decode a `LogGroupList` compressed with `compression`, `raw_size` bytes long once
+    /// decompressed.
+    pub fn decode_list_compressed(
+        buf: &[u8],
+        compression: Compression,
+        raw_size: usize,
+    ) -> Result<Vec<Self>, DecodeError> {
+        Self::decode_list(&compression.decompress(buf, raw_size)?)
+    }
+
+    /// Metadata shared by the logs.
+    pub fn metadata(&self) -> &LogGroupMetadata {
+        &self.metadata
+    }
+
+    /// Logs of the group.
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    /// Split the log group into its metadata and logs.
+    pub fn into_parts(self) -> (LogGroupMetadata, Vec<Log>) {
+        (self.metadata, self.logs)
+    }
 }
 
 // Manual implementation for faster encoding
@@ -246,19 +383,42 @@ pub(crate) fn encode_log_group<W: Write>(
 
 pub(crate) fn calc_log_group_encoded_len(metadata: &LogGroupMetadata, logs: &[Log]) -> usize {
     encoded_len_repeated(1u32, logs.iter(), logs.len())
-        + metadata
-            .topic
-            .is_empty()
+        + (!metadata.topic.is_empty())
             .then(|| encoded_str_len(3u32, &metadata.topic))
             .unwrap_or(0)
-        + metadata
-            .source
-            .is_empty()
+        + (!metadata.source.is_empty())
             .then(|| encoded_str_len(4u32, &metadata.source))
             .unwrap_or(0)
         + encoded_len_repeated(6u32, metadata.log_tags.iter(), metadata.log_tags.len())
 }
 
+/// Split logs into chunks, each encoded with the metadata into a log group of
+/// at most `max_logs` logs and `max_size` bytes.
+///
+/// A single log exceeding `max_size` is put in a chunk of its own.
+pub(crate) fn split_log_group<'a>(
+    metadata: &LogGroupMetadata,
+    logs: &'a [Log],
+    max_logs: usize,
+    max_size: usize,
+) -> Vec<&'a [Log]> {
+    let metadata_len = calc_log_group_encoded_len(metadata, &[]);
+    let mut chunks = Vec::new();
+    let (mut start, mut size) = (0, metadata_len);
+    for (i, log) in logs.iter().enumerate() {
+        let len = encoded_len_repeated(1u32, std::iter::once(log), 1);
+        if i > start && (i - start == max_logs || size + len > max_size) {
+            chunks.push(&logs[start..i]);
+            (start, size) = (i, metadata_len);
+        }
+        size += len;
+    }
+    if start < logs.len() || chunks.is_empty() {
+        chunks.push(&logs[start..]);
+    }
+    chunks
+}
+
 trait Message {
     fn encode_into_vec<W: Write>(&self, writer: &mut W) -> io::Result<()>;
     fn encoded_len(&self) -> usize;
@@ -313,6 +473,136 @@ impl Message for Log {
     }
 }
 
+fn decode_log_group(buf: &[u8]) -> Result<LogGroup, DecodeError> {
+    let mut reader = Reader(buf);
+    let mut group = LogGroup::default();
+    while !reader.is_empty() {
+        match reader.key()? {
+            (1, WireType::LengthDelimited) => group.logs.push(decode_log(reader.bytes()?)?),
+            (3, WireType::LengthDelimited) => group.metadata.topic = reader.str("Topic")?.into(),
+            (4, WireType::LengthDelimited) => group.metadata.source = reader.str("Source")?.into(),
+            (6, WireType::LengthDelimited) => {
+                let (key, value) = decode_pair(reader.bytes()?)?;
+                group
+                    .metadata
+                    .log_tags
+                    .insert(MayStaticKey::new(key), value.into());
+            }
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    Ok(group)
+}
+
+fn decode_log(buf: &[u8]) -> Result<Log, DecodeError> {
+    let mut reader = Reader(buf);
+    let mut timestamp = None;
+    let mut log = Log::new(0, None);
+    while !reader.is_empty() {
+        match reader.key()? {
+            (1, WireType::Varint) => timestamp = Some(reader.varint()? as u32),
+            (2, WireType::LengthDelimited) => {
+                let (key, value) = decode_pair(reader.bytes()?)?;
+                log.contents.insert(MayStaticKey::new(key), value.into());
+            }
+            (4, WireType::ThirtyTwoBit) => log.subsec_nanosecond = Some(reader.fixed32()?),
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    log.timestamp = timestamp.ok_or(DecodeError::MissingField("Time"))?;
+    Ok(log)
+}
+
+/// Decode a `LogContent` or `LogTag`, which share the same layout.
+fn decode_pair(buf: &[u8]) -> Result<(&str, &str), DecodeError> {
+    let mut reader = Reader(buf);
+    let (mut key, mut value) = (None, None);
+    while !reader.is_empty() {
+        match reader.key()? {
+            (1, WireType::LengthDelimited) => key = Some(reader.str("Key")?),
+            (2, WireType::LengthDelimited) => value = Some(reader.str("Value")?),
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    Ok((
+        key.ok_or(DecodeError::MissingField("Key"))?,
+        value.ok_or(DecodeError::MissingField("Value"))?,
+    ))
+}
+
+/// Cursor over a protobuf encoded message.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    #[inline]
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0;
+        for i in 0..10 {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7F) << (i * 7);
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::InvalidVarint)
+    }
+
+    #[inline]
+    fn key(&mut self) -> Result<(u32, WireType), DecodeError> {
+        let key = self.varint()?;
+        let wire_type = match key & 0x7 {
+            0 => WireType::Varint,
+            1 => WireType::SixtyFourBit,
+            2 => WireType::LengthDelimited,
+            5 => WireType::ThirtyTwoBit,
+            wire_type => return Err(DecodeError::InvalidWireType(wire_type as u8)),
+        };
+        Ok(((key >> 3) as u32, wire_type))
+    }
+
+    #[inline]
+    fn bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = self.varint()?;
+        self.take(usize::try_from(len).map_err(|_| DecodeError::UnexpectedEof)?)
+    }
+
+    #[inline]
+    fn str(&mut self, field: &'static str) -> Result<&'a str, DecodeError> {
+        std::str::from_utf8(self.bytes()?).map_err(|_| DecodeError::InvalidUtf8(field))
+    }
+
+    #[inline]
+    fn fixed32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn skip(&mut self, wire_type: WireType) -> Result<(), DecodeError> {
+        match wire_type {
+            WireType::Varint => self.varint().map(drop),
+            WireType::SixtyFourBit => self.take(8).map(drop),
+            WireType::LengthDelimited => self.bytes().map(drop),
+            WireType::ThirtyTwoBit => self.take(4).map(drop),
+            wire_type => Err(DecodeError::InvalidWireType(wire_type as u8)),
+        }
+    }
+}
+
 // Copy from prost
 
 #[allow(dead_code)]
@@ -421,6 +711,91 @@ fn encoded_fixed32_len(tag: u32) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_log_group() {
+        let metadata = LogGroupMetadata::new().with_topic("topic");
+        let logs = (0..10)
+            .map(|i| Log::new(i, None).with(MayStaticKey::from_static("message"), "hello world"))
+            .collect::<Vec<_>>();
+        let encoded_len = |logs: &[Log]| {
+            let mut buf = Vec::new();
+            encode_log_group(&mut buf, &metadata, logs).unwrap();
+            assert_eq!(buf.len(), calc_log_group_encoded_len(&metadata, logs));
+            buf.len()
+        };
+
+        let chunks = split_log_group(&metadata, &logs, 4, usize::MAX);
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            [4, 4, 2]
+        );
+
+        let max_size = encoded_len(&logs[..3]);
+        let chunks = split_log_group(&metadata, &logs, usize::MAX, max_size);
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            [3, 3, 3, 1]
+        );
+        assert!(chunks.iter().all(|chunk| encoded_len(chunk) <= max_size));
+
+        let chunks = split_log_group(&metadata, &logs[..2], usize::MAX, 1);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), [1, 1]);
+        assert_eq!(split_log_group(&metadata, &[], 4, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_decode() {
+        let metadata = LogGroupMetadata::new()
+            .with_topic("topic")
+            .with_source("127.0.0.1")
+            .with_tag(MayStaticKey::from_static("host"), "localhost");
+        let logs = vec![
+            Log::new(1700000000, Some(123))
+                .with(MayStaticKey::from_static("level"), "INFO")
+                .with(MayStaticKey::new("message"), "hello world"),
+            Log::new(1700000001, None),
+        ];
+        let mut buf = Vec::new();
+        encode_log_group(&mut buf, &metadata, &logs).unwrap();
+
+        let group = LogGroup::decode(&buf).unwrap();
+        assert_eq!(group, LogGroup::new(metadata.clone(), logs.clone()));
+        assert_eq!(group.metadata().tag("host"), Some("localhost"));
+        assert_eq!(group.logs()[0].get("message"), Some("hello world"));
+        assert_eq!(group.logs()[0].contents().count(), 2);
+
+        let compression = Compression::default();
+        let compressed = compression.compress(buf.clone());
+        let group = LogGroup::decode_compressed(&compressed, compression, buf.len()).unwrap();
+        assert_eq!(group, LogGroup::new(metadata.clone(), logs.clone()));
+
+        // LogGroupList with two groups
+        let mut list = Vec::new();
+        for _ in 0..2 {
+            encode_key(1, WireType::LengthDelimited, &mut list).unwrap();
+            encode_varint(buf.len() as u64, &mut list).unwrap();
+            list.extend_from_slice(&buf);
+        }
+        let groups = LogGroup::decode_list(&list).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].logs(), logs);
+
+        let compressed = compression.compress(list.clone());
+        let groups =
+            LogGroup::decode_list_compressed(&compressed, compression, list.len()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].logs(), logs);
+        assert!(matches!(
+            LogGroup::decode_list_compressed(&compressed, compression, list.len() - 1),
+            Err(DecodeError::Decompress(_))
+        ));
+
+        assert!(matches!(
+            LogGroup::decode(&buf[..buf.len() - 1]),
+            Err(DecodeError::UnexpectedEof)
+        ));
+    }
+
     #[test]
     fn test_size() {
         println!("size_of::<Log>() = {}", size_of::<Log>());
@@ -1,5 +1,5 @@
 //! A reporter for batching and sending logs to the SLS service.
-use crate::{Log, LogGroupMetadata, SlsClient};
+use crate::{Log, LogGroupMetadata, SlsClient, Target};
 use async_channel::{Receiver, Sender};
 use futures_util::{FutureExt, join, select};
 use std::{
@@ -30,6 +30,7 @@ pub struct Reporter {
     pub(crate) producer: Arc<Producer>,
     consumer: Arc<Mutex<Option<Consumer>>>,
     client: SlsClient,
+    target: Option<Target>,
 }
 
 /// Reporting is a handle to the reporting process, allowing configuration and starting the reporting.
@@ -37,6 +38,7 @@ pub struct Reporting {
     state: Arc<State>,
     consumer: Consumer,
     client: SlsClient,
+    target: Option<Target>,
 
     log_vec_capacity: usize,
     log_group_capacity: usize,
@@ -49,6 +51,7 @@ pub struct Reporting {
 struct LogConsumer {
     consumer: Consumer,
     client: SlsClient,
+    target: Option<Target>,
     vec_pool: Vec<Vec<Log>>,
     log_group: HashMap<Arc<LogGroupMetadata>, Vec<Log>>,
 
@@ -71,9 +74,18 @@ impl Reporter {
             producer: Arc::new(producer),
             consumer: Arc::new(Mutex::new(Some(consumer))),
             client,
+            target: None,
         }
     }
 
+    /// Send logs to the given target, instead of the default target of the client.
+    ///
+    /// Reporters of different targets can share one client.
+    pub fn with_target(mut self, target: Target) -> Self {
+        self.target = Some(target);
+        self
+    }
+
     /// Create the reporting future with a given drain timer.
     ///
     /// If the reporter is already in reporting state, it returns `None`.
@@ -88,6 +100,7 @@ impl Reporter {
             state: self.state.clone(),
             consumer,
             client: self.client.clone(),
+            target: self.target.clone(),
 
             log_vec_capacity: LOG_VEC_DEFAULT_CAPACITY,
             log_group_capacity: LOG_GROUP_DEFAULT_CAPACITY,
@@ -152,6 +165,7 @@ impl Reporting {
             state,
             consumer,
             client,
+            target,
             drain_timer,
             shutdown_signal,
             log_vec_capacity,
@@ -167,6 +181,7 @@ impl Reporting {
         let mut consumer = LogConsumer {
             consumer,
             client,
+            target,
             vec_pool,
             log_group,
 
@@ -220,7 +235,10 @@ impl LogConsumer {
 
     async fn drain(&mut self) {
         for (meta, mut log) in self.log_group.drain() {
-            self.client.put_log(&meta, &log).await;
+            match &self.target {
+                Some(target) => self.client.put_log_to(target, &meta, &log).await,
+                None => self.client.put_log(&meta, &log).await,
+            }
             log.clear();
             log.shrink_to(self.log_vec_capacity);
             self.vec_pool.push(log);
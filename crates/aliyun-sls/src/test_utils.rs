@@ -0,0 +1,85 @@
+//! Helpers for tests.
+use crate::{
+    SlsClientBuilder,
+    client::imp,
+    transport::{
+        HttpBackend, HttpConfig, HttpRequest, HttpResponse, HttpTransport, TransportFuture,
+    },
+};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Spawn a minimal HTTP server, answering each request with `handler(target)`.
+///
+/// `target` is the request target, i.e. the path and query of the request.
+/// Returns the base URL of the server.
+pub(crate) async fn serve(handler: fn(&str) -> (u16, String)) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 16 * 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let target = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = handler(target);
+            let response = format!(
+                "HTTP/1.1 {status} OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    base_url
+}
+
+/// Transport of the default backend, as used by a client without custom transport.
+pub(crate) fn transport() -> Arc<dyn HttpTransport> {
+    imp::transport(HttpBackend::default(), HttpConfig::default()).unwrap()
+}
+
+/// Builder of a client with dummy credentials, to the `cn-guangzhou` endpoint.
+pub(crate) fn test_client() -> SlsClientBuilder<'static> {
+    SlsClientBuilder::default()
+        .access_key("id")
+        .access_secret("secret")
+        .unwrap()
+        .endpoint("cn-guangzhou.log.aliyuncs.com")
+}
+
+/// Transport answering each request with `handler(request)`, and recording the requests.
+///
+/// Clones share the recorded requests, so a clone can be given to the client.
+#[derive(Clone)]
+pub(crate) struct FakeTransport {
+    handler: Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl FakeTransport {
+    pub(crate) fn new(
+        handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            requests: Arc::default(),
+        }
+    }
+
+    /// Requests sent so far.
+    pub(crate) fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl HttpTransport for FakeTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let res = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+        Box::pin(async { Ok(res) })
+    }
+}
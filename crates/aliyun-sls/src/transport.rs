@@ -0,0 +1,261 @@
+//! HTTP transport used to send requests to the SLS service.
+//!
+//! The client uses the transport of a built-in [`HttpBackend`] by default,
+//! a custom one can be set with
+//! [`SlsClientBuilder::transport`](crate::SlsClientBuilder::transport).
+use std::{error::Error, fmt, pin::Pin, time::Duration};
+
+/// Future returned by [`HttpTransport::send`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// Trait for sending HTTP requests.
+///
+/// Implementations must set the `content-length` header, and the `host` header unless
+/// provided by the request. Every other header is provided by the request.
+pub trait HttpTransport: Send + Sync + 'static {
+    /// Send the request, returning the response with the whole body.
+    ///
+    /// Non-successful responses must be returned as [`HttpResponse`] as well,
+    /// [`TransportError`] is reserved to requests which did not get a response.
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Built-in HTTP backend, each one is available with the feature of the same name.
+///
+/// Defaults to `reqwest` if it is enabled, `nyquest` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum HttpBackend {
+    /// [`reqwest`](https://docs.rs/reqwest)
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    #[cfg_attr(feature = "reqwest", default)]
+    Reqwest,
+    /// [`nyquest`](https://docs.rs/nyquest)
+    #[cfg(feature = "nyquest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "nyquest")))]
+    #[cfg_attr(not(feature = "reqwest"), default)]
+    Nyquest,
+}
+
+/// HTTP request method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Method {
+    /// `GET`
+    Get,
+    /// `POST`
+    Post,
+    /// `PUT`
+    Put,
+    /// `DELETE`
+    Delete,
+}
+
+/// HTTP request to send.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// Request method.
+    pub method: Method,
+    /// Full url of the request, including the query.
+    pub url: String,
+    /// Request headers, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    /// Request body, empty if the request has no body.
+    pub body: Vec<u8>,
+    /// Timeout of the whole request, overriding the one of the transport.
+    pub timeout: Option<Duration>,
+    /// Whether the body of a successful response is needed.
+    ///
+    /// If `false`, the transport may skip reading it and return an empty body.
+    /// The body of a non-successful response is always needed.
+    pub read_body: bool,
+}
+
+/// HTTP response received.
+#[derive(Debug, Clone, Default)]
+pub struct HttpResponse {
+    /// Response status code.
+    pub status: u16,
+    /// Response headers.
+    pub headers: Vec<(String, String)>,
+    /// Response body.
+    pub body: Vec<u8>,
+}
+
+/// Configuration of the built-in HTTP transports.
+///
+/// Not every backend supports every option, see the documentation of each option.
+/// Building a client fails if an option is not supported by its backend.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) proxy: Option<String>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+}
+
+/// Error type for sending HTTP requests.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum TransportError {
+    /// The request timed out.
+    #[error("request timed out: {0}")]
+    Timeout(#[source] Box<dyn Error + Send + Sync>),
+    /// Failed to connect to the server.
+    #[error("failed to connect: {0}")]
+    Connect(#[source] Box<dyn Error + Send + Sync>),
+    /// Any other error.
+    #[error("http transport error: {0}")]
+    Other(#[source] Box<dyn Error + Send + Sync>),
+}
+
+impl Method {
+    /// Method name, e.g. `GET`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+        }
+    }
+
+    /// Whether the request can be retried safely, i.e. `GET` and `PUT`.
+    ///
+    /// `DELETE` is not, as its retry fails once the first attempt was applied.
+    pub(crate) fn is_idempotent(&self) -> bool {
+        matches!(self, Method::Get | Method::Put)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl HttpConfig {
+    /// Create a new configuration, using the defaults of the backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timeout of connecting to the server.
+    ///
+    /// Only supported by `reqwest`.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Set the timeout of each request, from connecting to reading the whole response.
+    ///
+    /// No timeout by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the url of the proxy used for both HTTP and HTTPS requests,
+    /// e.g. `http://127.0.0.1:8080`.
+    ///
+    /// Only supported by `reqwest`.
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Set how long idle connections are kept in the pool.
+    ///
+    /// Only supported by `reqwest`.
+    pub fn with_pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    /// Set the maximum number of idle connections kept for each host.
+    ///
+    /// Only supported by `reqwest`.
+    pub fn with_pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    /// Trust an extra root certificate, in PEM format.
+    ///
+    /// Only supported by `reqwest` with a TLS feature enabled.
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Set the interval of TCP keepalive probes.
+    ///
+    /// Only supported by `reqwest`.
+    pub fn with_tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+}
+
+impl HttpResponse {
+    /// Create a response with the given status, and no header or body.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            ..Default::default()
+        }
+    }
+
+    /// Add a header to the response.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the body of the response.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Whether the status code is in `200..300`.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Value of the first header with the given name, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Body as text, invalid UTF-8 is replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+impl TransportError {
+    /// Create a [`TransportError::Timeout`] from any error.
+    pub fn timeout(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        TransportError::Timeout(err.into())
+    }
+
+    /// Create a [`TransportError::Connect`] from any error.
+    pub fn connect(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        TransportError::Connect(err.into())
+    }
+
+    /// Create a [`TransportError::Other`] from any error.
+    pub fn other(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        TransportError::Other(err.into())
+    }
+}